@@ -15,6 +15,9 @@ import CircularProgress from "@mui/material/CircularProgress";
 import Fab from "@mui/material/Fab";
 import AddIcon from "@mui/icons-material/Add";
 import RabbitAppBar from "./components/fragments/RabbitAppBar";
+import { clusterPath } from "./utilities/clusters";
+import { currentUser } from "./utilities/auth";
+import Login from "./components/Login";
 
 export default function App() {
   const [vhosts, setVhosts] = useState([]);
@@ -23,14 +26,19 @@ export default function App() {
   const [selectedQueue, setSelectedQueue] = useState("");
   const navigate = useNavigate();
   const [isQueuesLoading, setIsQueuesLoading] = useState(false);
+  const [user, setUser] = useState(null);
 
   useEffect(() => {
     async function responses() {
-      let response = await fetch("/vhosts");
-      setVhosts(await response.json());
+      const loggedInUser = await currentUser();
+      setUser(loggedInUser);
+      if (loggedInUser) {
+        let response = await fetch(`${await clusterPath()}/vhosts`);
+        setVhosts(await response.json());
+      }
     }
     responses();
-  }, []);
+  }, [user?.name]);
 
   /**
    * Handles the change event for the virtual host selection.
@@ -69,7 +77,9 @@ export default function App() {
    * @param {string} vhost - The name of the virtual host for which to retrieve the queues.
    */
   const getQueues = async (vhost) => {
-    let response = await fetch(`/queues/${vhost}`);
+    let response = await fetch(
+      `${await clusterPath()}/queues/${encodeURIComponent(vhost)}`
+    );
     setQueues(await response.json());
     setIsQueuesLoading(false);
   };
@@ -116,11 +126,14 @@ export default function App() {
                 element={
                   <>
                     <Card variant="outlined">
-                      <Vhosts
-                        selectedVhost={selectedVhost}
-                        handleChange={handleVhostChange}
-                        vhosts={vhosts}
-                      />
+                      {!user && <Login handleLogin={setUser} />}
+                      {user && (
+                        <Vhosts
+                          selectedVhost={selectedVhost}
+                          handleChange={handleVhostChange}
+                          vhosts={vhosts}
+                        />
+                      )}
                       {isQueuesLoading && <CircularProgress />}
                       {queues && (
                         <Queues
@@ -0,0 +1,49 @@
+import { useState } from "react";
+import { Alert, Button, TextField, Typography } from "@mui/material";
+import { login } from "../utilities/auth";
+
+export default function Login({ handleLogin }) {
+  const [username, setUsername] = useState("");
+  const [password, setPassword] = useState("");
+  const [error, setError] = useState("");
+
+  /**
+   * Submits the credentials and reports the logged in user to the parent.
+   *
+   * @param {Object} event - The form submit event.
+   */
+  const handleSubmit = async (event) => {
+    event.preventDefault();
+    const { ok, body } = await login(username, password);
+    if (ok) {
+      handleLogin(body);
+    } else {
+      setError(body.message);
+    }
+  };
+
+  return (
+    <form onSubmit={handleSubmit}>
+      <Typography variant="h6">Log in</Typography>
+      {error && <Alert severity="error">{error}</Alert>}
+      <TextField
+        label="Username"
+        value={username}
+        onChange={(event) => setUsername(event.target.value)}
+        fullWidth
+        margin="normal"
+      />
+      <TextField
+        label="Password"
+        type="password"
+        value={password}
+        onChange={(event) => setPassword(event.target.value)}
+        fullWidth
+        margin="normal"
+      />
+      <Button type="submit" variant="contained" color="warning">
+        Log in
+      </Button>
+    </form>
+  );
+}
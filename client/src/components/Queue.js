@@ -8,6 +8,7 @@ import Button from "@mui/material/Button";
 import Typography from "@mui/material/Typography";
 import TextField from "@mui/material/TextField";
 import { filterMessages } from "../utilities/messages";
+import { clusterPath } from "../utilities/clusters";
 import MessageViewer from "./message-viewers/MessageViewer";
 import ReusableModal from "./fragments/ReusableModal";
 import ReusableAlertComponent from "./fragments/ReusableAlertComponent";
@@ -54,7 +55,11 @@ export default function Queue() {
     if (count === 0) {
       handleOpen();
     } else {
-      let response = await fetch(`/queues/${vhost}/${queue}?count=${count}`);
+      let response = await fetch(
+        `${await clusterPath()}/queues/${encodeURIComponent(
+          vhost
+        )}/${encodeURIComponent(queue)}?count=${count}`
+      );
       let respMessages = await response.json();
       setMessages(respMessages);
       setMessageTypes(respMessages);
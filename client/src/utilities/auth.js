@@ -0,0 +1,34 @@
+/**
+ * Fetches the user the browser is logged in to the server as.
+ *
+ * @returns {Promise<Object|null>} The principal (`name` and `method`), or `null` if not logged in.
+ */
+const currentUser = async () => {
+  const response = await fetch("/auth/session");
+  return response.ok ? response.json() : null;
+};
+
+/**
+ * Logs in to the server with a username and password from its users file.
+ *
+ * On success the server sets a session cookie used by all further requests.
+ *
+ * @param {string} username - The username.
+ * @param {string} password - The password.
+ * @returns {Promise<Object>} `ok`, and the principal or an error body with a `message` as `body`.
+ */
+const login = async (username, password) => {
+  const response = await fetch("/auth/session", {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ username, password }),
+  });
+  return { ok: response.ok, body: await response.json() };
+};
+
+/**
+ * Logs out of the server.
+ */
+const logout = () => fetch("/auth/session", { method: "DELETE" });
+
+export { currentUser, login, logout };
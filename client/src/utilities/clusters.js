@@ -0,0 +1,20 @@
+let clusterPathPromise;
+
+/**
+ * Resolves the API path prefix of the cluster the client works with.
+ *
+ * The server scopes vhosts and queues under `/clusters/<id>`. The client uses
+ * the first cluster returned by `/clusters`; the lookup is done once and cached.
+ *
+ * @returns {Promise<string>} The path prefix, e.g. `/clusters/default`.
+ */
+const clusterPath = () => {
+  if (!clusterPathPromise) {
+    clusterPathPromise = fetch("/clusters")
+      .then((response) => response.json())
+      .then((clusters) => `/clusters/${encodeURIComponent(clusters[0].id)}`);
+  }
+  return clusterPathPromise;
+};
+
+export { clusterPath };
@@ -0,0 +1,222 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{config::AuditConfig, exceptions::ServerError};
+
+/// Whether an audited action succeeded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// The action completed (2xx or 3xx response).
+    Success,
+    /// The action was rejected or failed.
+    Failure,
+}
+
+/// One action performed through the API.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct AuditRecord {
+    /// When the action completed.
+    #[serde(with = "time::serde::rfc3339")]
+    pub time: OffsetDateTime,
+    /// Name of the principal, if the request was authenticated.
+    pub principal: Option<String>,
+    /// Operation performed, i.e. the name of the route, e.g. `messages`.
+    pub operation: String,
+    /// HTTP method and path of the request.
+    pub request: String,
+    /// Cluster the action targeted.
+    pub cluster: Option<String>,
+    /// Vhost the action targeted.
+    pub vhost: Option<String>,
+    /// Queue the action targeted.
+    pub queue: Option<String>,
+    /// Query parameters of the request, and the parameters of its body that describe the operation.
+    #[serde(default)]
+    pub parameters: BTreeMap<String, String>,
+    /// Whether the action succeeded.
+    pub outcome: AuditOutcome,
+    /// HTTP status of the response.
+    pub status: u16,
+    /// Error message, if the action failed.
+    pub error: Option<String>,
+}
+
+/// Criteria to select audit records with. Unset criteria match every record.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// Only records at or after this time.
+    pub since: Option<OffsetDateTime>,
+    /// Only records before this time.
+    pub until: Option<OffsetDateTime>,
+    /// Only records of this principal.
+    pub principal: Option<String>,
+    /// Only records targeting this cluster.
+    pub cluster: Option<String>,
+    /// Only records targeting this vhost.
+    pub vhost: Option<String>,
+    /// Only records targeting this queue.
+    pub queue: Option<String>,
+    /// Maximum number of records returned; the most recent ones are kept.
+    pub limit: usize,
+}
+
+impl AuditQuery {
+    /// Indicates if a record matches every criterion.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        let equals = |criterion: &Option<String>, value: &Option<String>| {
+            criterion.is_none() || criterion == value
+        };
+        self.since.is_none_or(|since| record.time >= since)
+            && self.until.is_none_or(|until| record.time < until)
+            && equals(&self.principal, &record.principal)
+            && equals(&self.cluster, &record.cluster)
+            && equals(&self.vhost, &record.vhost)
+            && equals(&self.queue, &record.queue)
+    }
+}
+
+/// Append-only JSON-lines audit log. Managed as Rocket state.
+///
+/// Records are appended to `path`, one JSON object per line. Once the file grows past `max_bytes` it
+/// is renamed to `path.1` (shifting older files to `path.2` and so on) and a new file is started;
+/// only `max_files` rotated files are kept.
+#[derive(Debug)]
+pub struct AuditLog {
+    /// Path of the current log file.
+    path: PathBuf,
+    /// Size after which the log file is rotated.
+    max_bytes: u64,
+    /// Number of rotated files kept.
+    max_files: usize,
+    /// The current log file, opened for appending.
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    /// Opens (or creates) the audit log at `config.path`, or returns `None` if auditing is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Configuration` if the file cannot be opened.
+    pub fn open(config: &AuditConfig) -> Result<Option<AuditLog>, ServerError> {
+        let Some(path) = &config.path else {
+            return Ok(None);
+        };
+        Ok(Some(AuditLog {
+            path: path.clone(),
+            max_bytes: config.max_bytes,
+            max_files: config.max_files,
+            file: Mutex::new(open_append(path)?),
+        }))
+    }
+
+    /// Appends a record, rotating the log first if it is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Configuration` if the record cannot be written.
+    pub fn append(&self, record: &AuditRecord) -> Result<(), ServerError> {
+        let mut line = serde_json::to_string(record).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+        line.push('\n');
+
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let size = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+            *file = open_append(&self.path)?;
+        }
+        file.write_all(line.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| self.error("write", e))
+    }
+
+    /// Returns the most recent records matching `query`, oldest first.
+    ///
+    /// Lines that cannot be parsed (e.g. truncated by a crash) are skipped.
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, ServerError> {
+        // Hold the lock so that the files are not rotated while they are read.
+        let _file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        let mut records = VecDeque::with_capacity(query.limit);
+        for path in (1..=self.max_files)
+            .rev()
+            .map(|index| self.rotated_path(index))
+            .chain([self.path.clone()])
+        {
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(_) => continue,
+            };
+            for line in BufReader::new(file).lines() {
+                let line = line.map_err(|e| self.error("read", e))?;
+                let Ok(record) = serde_json::from_str::<AuditRecord>(&line) else {
+                    continue;
+                };
+                if query.matches(&record) {
+                    if records.len() == query.limit {
+                        records.pop_front();
+                    }
+                    records.push_back(record);
+                }
+            }
+        }
+        Ok(records.into())
+    }
+
+    /// Shifts the rotated files by one and moves the current file to `path.1`.
+    fn rotate(&self) -> Result<(), ServerError> {
+        let _ = fs::remove_file(self.rotated_path(self.max_files));
+        for index in (1..self.max_files).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                fs::rename(&from, self.rotated_path(index + 1))
+                    .map_err(|e| self.error("rotate", e))?;
+            }
+        }
+        if self.max_files == 0 {
+            fs::remove_file(&self.path).map_err(|e| self.error("rotate", e))
+        } else {
+            fs::rename(&self.path, self.rotated_path(1)).map_err(|e| self.error("rotate", e))
+        }
+    }
+
+    /// Path of the `index`th rotated file, e.g. `audit.jsonl.1`.
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    fn error(&self, action: &str, error: std::io::Error) -> ServerError {
+        ServerError::Configuration {
+            reason: format!(
+                "cannot {} audit log {}: {}",
+                action,
+                self.path.display(),
+                error
+            ),
+        }
+    }
+}
+
+/// Opens a file for appending, creating it if needed.
+fn open_append(path: &Path) -> Result<File, ServerError> {
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| ServerError::Configuration {
+            reason: format!("cannot open audit log {}: {}", path.display(), e),
+        })
+}
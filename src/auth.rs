@@ -0,0 +1,305 @@
+use core::fmt;
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::{
+    config::{ApiTokenConfig, AuthConfig, RoleGrant},
+    exceptions::ServerError,
+    pattern::glob_matches,
+};
+
+/// Name of the principal used for every request when authentication is disabled.
+pub const ANONYMOUS: &str = "anonymous";
+
+/// Password of the dummy hash checked for unknown usernames.
+const DUMMY_PASSWORD: &str = "not-a-user";
+
+/// What a principal may do on a cluster or vhost. Each role includes the ones before it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum Role {
+    /// May list and peek without side effects.
+    Viewer,
+    /// May also remove, move, publish and purge messages.
+    Operator,
+    /// May also change queues and policies.
+    Admin,
+}
+
+/// How a principal authenticated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum AuthMethod {
+    /// Authentication is disabled.
+    Anonymous,
+    /// `Authorization: Bearer` with a static API token.
+    Token,
+    /// `Authorization: Basic` checked against the users file.
+    Basic,
+    /// Session cookie obtained by logging in.
+    Session,
+}
+
+/// The authenticated client of a request.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct Principal {
+    /// Name of the user or API token.
+    pub name: String,
+    /// How the principal authenticated.
+    pub method: AuthMethod,
+}
+
+impl Principal {
+    /// Creates a principal.
+    pub fn new(name: &str, method: AuthMethod) -> Principal {
+        Principal {
+            name: name.to_string(),
+            method,
+        }
+    }
+}
+
+/// Checks the credentials clients present to this server. Managed as Rocket state.
+///
+/// API tokens are compared in constant time; passwords are checked against the bcrypt hashes of the
+/// users file (as written by `htpasswd -B`), which is read once at startup.
+pub struct Authenticator {
+    /// Whether clients have to authenticate at all.
+    enabled: bool,
+    /// Static API tokens.
+    tokens: Vec<ApiTokenConfig>,
+    /// bcrypt hashes of the users file, by username.
+    users: HashMap<String, String>,
+    /// bcrypt hash checked for unknown usernames, with the cost of the users file, so that the time
+    /// taken to reject a login does not reveal whether the username exists. `None` without users.
+    dummy_hash: Option<String>,
+}
+
+impl Authenticator {
+    /// Creates the authenticator, reading the users file if one is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Configuration` if the users file cannot be read or is malformed.
+    pub fn from_config(config: &AuthConfig) -> Result<Authenticator, ServerError> {
+        let users = match &config.users_file {
+            Some(path) => read_users_file(path)?,
+            None => HashMap::new(),
+        };
+        let dummy_hash = users
+            .values()
+            .next()
+            .map(|hash| {
+                let cost = hash
+                    .parse::<bcrypt::HashParts>()
+                    .map_or(bcrypt::DEFAULT_COST, |parts| parts.get_cost());
+                bcrypt::hash(DUMMY_PASSWORD, cost)
+            })
+            .transpose()
+            .map_err(|e| ServerError::Configuration {
+                reason: format!("cannot hash the dummy password: {}", e),
+            })?;
+        Ok(Authenticator {
+            enabled: config.is_enabled(),
+            tokens: config.api_tokens.clone(),
+            users,
+            dummy_hash,
+        })
+    }
+
+    /// Indicates if clients have to authenticate.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Authenticates a static API token.
+    pub fn authenticate_token(&self, token: &str) -> Option<Principal> {
+        // Every token is compared so that timing does not reveal which one was close.
+        self.tokens
+            .iter()
+            .fold(None, |found, candidate| {
+                let matches: bool = candidate.token.as_bytes().ct_eq(token.as_bytes()).into();
+                found.or(matches.then_some(candidate))
+            })
+            .map(|candidate| Principal::new(&candidate.name, AuthMethod::Token))
+    }
+
+    /// Authenticates a username and password against the users file.
+    ///
+    /// bcrypt is deliberately slow, so the hash is checked on a blocking thread. Unknown usernames
+    /// are checked against a dummy hash, to take as long as a wrong password.
+    pub async fn authenticate_password(
+        &self,
+        username: &str,
+        password: &str,
+        method: AuthMethod,
+    ) -> Option<Principal> {
+        let (hash, known) = match self.users.get(username) {
+            Some(hash) => (hash.clone(), true),
+            None => (self.dummy_hash.clone()?, false),
+        };
+        let password = password.to_string();
+        let verified = tokio::task::spawn_blocking(move || bcrypt::verify(password, &hash)).await;
+        (known && matches!(verified, Ok(Ok(true)))).then(|| Principal::new(username, method))
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Viewer => write!(f, "viewer"),
+            Role::Operator => write!(f, "operator"),
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("enabled", &self.enabled)
+            .field("tokens", &self.tokens)
+            .field("users", &self.users.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Reads an htpasswd-style users file.
+fn read_users_file(path: &Path) -> Result<HashMap<String, String>, ServerError> {
+    let contents = fs::read_to_string(path).map_err(|e| ServerError::Configuration {
+        reason: format!("cannot read users file {}: {}", path.display(), e),
+    })?;
+    parse_users(&contents).map_err(|reason| ServerError::Configuration {
+        reason: format!("users file {}: {}", path.display(), reason),
+    })
+}
+
+/// Parses `username:bcrypt-hash` lines. Blank lines and lines starting with `#` are ignored.
+///
+/// # Example
+///
+/// ```rust
+/// use rabbitmq_messages_management::auth::parse_users;
+/// let hash = bcrypt::hash("secret", 4).unwrap();
+/// let users = parse_users(&format!("# viewers\nalice:{}\n", hash)).unwrap();
+/// assert_eq!(users["alice"], hash);
+/// assert!(parse_users("bob:{SHA}abc").is_err());
+/// ```
+pub fn parse_users(contents: &str) -> Result<HashMap<String, String>, String> {
+    let mut users = HashMap::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (username, hash) = line
+            .split_once(':')
+            .ok_or_else(|| format!("line {}: expected username:hash", index + 1))?;
+        if !["$2a$", "$2b$", "$2y$"]
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+        {
+            return Err(format!(
+                "line {}: only bcrypt hashes are supported (htpasswd -B)",
+                index + 1
+            ));
+        }
+        if users
+            .insert(username.to_string(), hash.to_string())
+            .is_some()
+        {
+            return Err(format!(
+                "line {}: user {:?} is listed twice",
+                index + 1,
+                username
+            ));
+        }
+    }
+    Ok(users)
+}
+
+/// Resolves the role of a principal on a cluster or vhost. Managed as Rocket state.
+///
+/// The role is the highest of the default role and every grant matching the principal, the cluster
+/// and the vhost.
+#[derive(Debug)]
+pub struct Authorizer {
+    /// Role before grants are applied.
+    default_role: Option<Role>,
+    /// Configured grants.
+    grants: Vec<RoleGrant>,
+}
+
+impl Authorizer {
+    /// Creates the authorizer from the authentication settings.
+    pub fn from_config(config: &AuthConfig) -> Authorizer {
+        Authorizer {
+            default_role: config.default_role,
+            grants: config.grants.clone(),
+        }
+    }
+
+    /// Role of a principal on a vhost of a cluster.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::{auth::{Authorizer, Role}, config::{AuthConfig, RoleGrant}};
+    /// let authorizer = Authorizer::from_config(&AuthConfig {
+    ///     grants: vec![RoleGrant {
+    ///         principal: "alice".to_string(),
+    ///         cluster: "prod".to_string(),
+    ///         vhost: "orders-*".to_string(),
+    ///         role: Role::Operator,
+    ///     }],
+    ///     ..AuthConfig::default()
+    /// });
+    /// assert_eq!(authorizer.role("alice", "prod", "orders-eu"), Some(Role::Operator));
+    /// assert_eq!(authorizer.role("alice", "prod", "payments"), Some(Role::Viewer));
+    /// ```
+    pub fn role(&self, principal: &str, cluster: &str, vhost: &str) -> Option<Role> {
+        self.resolve(principal, cluster, |grant| {
+            glob_matches(&grant.vhost, vhost)
+        })
+    }
+
+    /// Highest role of a principal on any vhost of a cluster, i.e. whether it may see the cluster at all.
+    pub fn cluster_role(&self, principal: &str, cluster: &str) -> Option<Role> {
+        self.resolve(principal, cluster, |_| true)
+    }
+
+    /// Role of a principal on every vhost of every one of `clusters`, for the routes that are not about
+    /// a single cluster (e.g. the audit log).
+    ///
+    /// Only the default role and the grants for all vhosts (`vhost = "*"`) count, and the role is the
+    /// lowest over the clusters: a grant on some vhosts of a cluster gives no role over the others.
+    pub fn global_role<'a, I>(&self, principal: &str, clusters: I) -> Option<Role>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        clusters
+            .into_iter()
+            .map(|cluster| self.resolve(principal, cluster, |grant| grant.vhost == "*"))
+            .min()
+            .unwrap_or(self.default_role)
+    }
+
+    fn resolve<F>(&self, principal: &str, cluster: &str, vhost_matches: F) -> Option<Role>
+    where
+        F: Fn(&RoleGrant) -> bool,
+    {
+        self.grants
+            .iter()
+            .filter(|grant| {
+                glob_matches(&grant.principal, principal)
+                    && glob_matches(&grant.cluster, cluster)
+                    && vhost_matches(grant)
+            })
+            .map(|grant| grant.role)
+            .chain(self.default_role)
+            .max()
+    }
+}
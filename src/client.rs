@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use isahc::{
+    config::{CaCertificate, ClientCertificate, Configurable, PrivateKey, SslOption},
+    http::request::Builder,
+    AsyncBody, HttpClient, Request,
+};
+use serde::Deserialize;
+
+use crate::{
+    basic_authorization,
+    config::ManagementConfig,
+    exceptions::ServerError,
+    invalid_uri,
+    logging::{next_request_id, UpstreamCall},
+    prepare_url,
+    rabbitmq::path::ApiPath,
+    read_response,
+};
+
+/// Default timeout for a whole management API request.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default timeout for establishing a connection to the management API.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Client for the RabbitMQ management API.
+///
+/// Owns the base URL, the credentials and a shared [`HttpClient`], so connections to the broker are
+/// reused between requests and the `Authorization` header is computed once. The client is cheap to
+/// share: it is managed as Rocket state and handed to the route handlers.
+#[derive(Debug, Clone)]
+pub struct ManagementClient {
+    /// Root URL of the management API, e.g. `http://localhost:15672`.
+    root: String,
+    /// Pooled HTTP client carrying the default headers and timeouts.
+    http: HttpClient,
+    /// `Authorization` header overriding the configured credentials, see [`ManagementClient::with_credentials`].
+    authorization: Option<String>,
+    /// Id of the API request this client serves, logged with each call, see [`ManagementClient::with_request_id`].
+    request_id: Option<String>,
+}
+
+impl ManagementClient {
+    /// Creates a new client for the management API at `root`, authenticating with the given credentials
+    /// and using the default timeouts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Transport` if the underlying HTTP client cannot be created.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::client::ManagementClient;
+    /// let client = ManagementClient::new("http://localhost:15672", "guest", "guest").unwrap();
+    /// assert_eq!(client.root(), "http://localhost:15672");
+    /// ```
+    pub fn new(
+        root: &str,
+        username: &str,
+        password: &str,
+    ) -> Result<ManagementClient, ServerError> {
+        ManagementClient::from_config(&ManagementConfig::new(root, username, password))
+    }
+
+    /// Creates a new client from the management section of the application configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Transport` if the underlying HTTP client cannot be created.
+    pub fn from_config(config: &ManagementConfig) -> Result<ManagementClient, ServerError> {
+        let mut builder = HttpClient::builder()
+            .default_header(
+                "Authorization",
+                basic_authorization(&config.username, &config.password),
+            )
+            .default_header("Content-Type", "application/json")
+            .timeout(config.timeout())
+            .connect_timeout(config.connect_timeout());
+
+        if let Some(ca_certificate) = &config.tls.ca_certificate {
+            builder = builder.ssl_ca_certificate(CaCertificate::file(ca_certificate));
+        }
+        if let (Some(certificate), Some(key)) =
+            (&config.tls.client_certificate, &config.tls.client_key)
+        {
+            builder = builder.ssl_client_certificate(ClientCertificate::pem_file(
+                certificate,
+                PrivateKey::pem_file(key, None),
+            ));
+        }
+        if config.tls.accept_invalid_certs {
+            builder = builder.ssl_options(SslOption::DANGER_ACCEPT_INVALID_CERTS);
+        }
+
+        let http = builder
+            .build()
+            .map_err(|e| ServerError::from_transport(&config.root, e))?;
+
+        Ok(ManagementClient {
+            root: config.root.clone(),
+            http,
+            authorization: None,
+            request_id: None,
+        })
+    }
+
+    /// Returns a client authenticating with the given credentials instead of the configured ones.
+    ///
+    /// The returned client shares the connection pool of this one, so creating it per request is cheap.
+    pub fn with_credentials(&self, username: &str, password: &str) -> ManagementClient {
+        ManagementClient {
+            authorization: Some(basic_authorization(username, password)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client logging its calls with the id of the API request they serve.
+    pub fn with_request_id(&self, request_id: &str) -> ManagementClient {
+        ManagementClient {
+            request_id: Some(request_id.to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Root URL of the management API.
+    pub fn root(&self) -> &str {
+        &self.root
+    }
+
+    /// Sends a GET request to `path` (relative to the root) and deserializes the response into `T`.
+    pub async fn get<T>(&self, path: &ApiPath) -> Result<T, ServerError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let uri = prepare_url(&self.root, &path.to_string())?;
+        let request = self
+            .authorize(Request::get(&uri))
+            .body(())
+            .map_err(|e| invalid_uri(&uri, e.to_string()))?;
+
+        let call = UpstreamCall::start(&self.request_id(), &request, None);
+        let response = self
+            .http
+            .send_async(request)
+            .await
+            .map_err(|e| ServerError::from_transport(&uri, e));
+        call.finish(&response);
+
+        read_response(&uri, response?).await
+    }
+
+    /// Sends a DELETE request to `path` (relative to the root) and deserializes the response into `T`.
+    pub async fn delete<T>(&self, path: &ApiPath) -> Result<T, ServerError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let uri = prepare_url(&self.root, &path.to_string())?;
+        let request = self
+            .authorize(Request::delete(&uri))
+            .body(())
+            .map_err(|e| invalid_uri(&uri, e.to_string()))?;
+
+        let call = UpstreamCall::start(&self.request_id(), &request, None);
+        let response = self
+            .http
+            .send_async(request)
+            .await
+            .map_err(|e| ServerError::from_transport(&uri, e));
+        call.finish(&response);
+
+        read_response(&uri, response?).await
+    }
+
+    /// Sends a POST request with `body` to `path` (relative to the root) and deserializes the response into `T`.
+    pub async fn post<T, B>(&self, path: &ApiPath, body: B) -> Result<T, ServerError>
+    where
+        AsyncBody: From<B>,
+        B: AsRef<[u8]>,
+        T: for<'de> Deserialize<'de>,
+    {
+        let uri = prepare_url(&self.root, &path.to_string())?;
+        let request = self
+            .authorize(Request::post(&uri))
+            .body(body)
+            .map_err(|e| invalid_uri(&uri, e.to_string()))?;
+
+        let call = UpstreamCall::start(&self.request_id(), &request, Some(request.body().as_ref()));
+        let response = self
+            .http
+            .send_async(request.map(AsyncBody::from))
+            .await
+            .map_err(|e| ServerError::from_transport(&uri, e));
+        call.finish(&response);
+
+        read_response(&uri, response?).await
+    }
+
+    /// Id logged with the next call: the id of the API request if there is one, a fresh one otherwise.
+    fn request_id(&self) -> String {
+        self.request_id.clone().unwrap_or_else(next_request_id)
+    }
+
+    /// Sets the overriding `Authorization` header on a request, if there is one.
+    fn authorize(&self, builder: Builder) -> Builder {
+        match &self.authorization {
+            Some(authorization) => builder.header("Authorization", authorization),
+            None => builder,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use serde::Serialize;
+
+use crate::{client::ManagementClient, config::AppConfig, exceptions::ServerError};
+
+/// A RabbitMQ cluster this server can talk to.
+#[derive(Debug)]
+pub struct Cluster {
+    /// Identifier used in URLs.
+    id: String,
+    /// Human readable name.
+    name: String,
+    /// Client for the cluster's management API.
+    client: ManagementClient,
+}
+
+impl Cluster {
+    /// Identifier used in URLs.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Client for the cluster's management API.
+    pub fn client(&self) -> &ManagementClient {
+        &self.client
+    }
+}
+
+/// Summary of a cluster, as returned by `GET /clusters`.
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ClusterSummary {
+    /// Identifier used in URLs
+    id: String,
+    /// Human readable name
+    name: String,
+    /// Root URL of the management API
+    root: String,
+}
+
+impl ClusterSummary {
+    /// Identifier used in URLs.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// All clusters this server manages, in configuration order. Managed as Rocket state.
+#[derive(Debug)]
+pub struct Clusters {
+    clusters: Vec<Cluster>,
+}
+
+impl Clusters {
+    /// Creates a management API client for every configured cluster.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Transport` if a client cannot be created.
+    pub fn from_config(config: &AppConfig) -> Result<Clusters, ServerError> {
+        let clusters = config
+            .all_clusters()
+            .into_iter()
+            .map(|cluster| {
+                Ok(Cluster {
+                    name: cluster.name.unwrap_or_else(|| cluster.id.clone()),
+                    client: ManagementClient::from_config(&cluster.management)?,
+                    id: cluster.id,
+                })
+            })
+            .collect::<Result<Vec<Cluster>, ServerError>>()?;
+        Ok(Clusters { clusters })
+    }
+
+    /// Looks up a cluster by id.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::UnknownCluster` if no cluster has this id.
+    pub fn get(&self, id: &str) -> Result<&Cluster, ServerError> {
+        self.clusters
+            .iter()
+            .find(|cluster| cluster.id == id)
+            .ok_or_else(|| ServerError::UnknownCluster { id: id.to_string() })
+    }
+
+    /// Looks up the management API client of a cluster by id.
+    pub fn client(&self, id: &str) -> Result<&ManagementClient, ServerError> {
+        self.get(id).map(Cluster::client)
+    }
+
+    /// Ids of all clusters, in configuration order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.clusters.iter().map(Cluster::id)
+    }
+
+    /// Summaries of all clusters, in configuration order.
+    pub fn summaries(&self) -> Vec<ClusterSummary> {
+        self.clusters
+            .iter()
+            .map(|cluster| ClusterSummary {
+                id: cluster.id.clone(),
+                name: cluster.name.clone(),
+                root: cluster.client.root().to_string(),
+            })
+            .collect()
+    }
+}
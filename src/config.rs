@@ -0,0 +1,637 @@
+use core::fmt;
+use std::{collections::HashSet, net::IpAddr, path::PathBuf, time::Duration};
+
+use isahc::http::Uri;
+use rocket::figment::{providers::Env, Figment};
+use serde::{de::IntoDeserializer, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    auth::Role,
+    client::{DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT},
+    constants::RABBITMQ_MANAGEMENT_ENV_PREFIX,
+    exceptions::ServerError,
+};
+
+/// Application configuration, loaded once at startup.
+///
+/// Values are read through Rocket's figment, so they can come from `Rocket.toml` (or the file named by
+/// `ROCKET_CONFIG`), from `ROCKET_*` environment variables and, for the management API settings, from
+/// the `RABBITMQ_MANAGEMENT_*` environment variables (which may be set in `.env`).
+///
+/// Brokers are listed in `clusters`; a lone `management` table (or the `RABBITMQ_MANAGEMENT_*`
+/// variables) is treated as a cluster with the id [`DEFAULT_CLUSTER_ID`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AppConfig {
+    /// Connection settings for a single RabbitMQ management API.
+    #[serde(default)]
+    pub management: Option<ManagementConfig>,
+    /// Named RabbitMQ clusters managed by this server.
+    #[serde(default)]
+    pub clusters: Vec<ClusterConfig>,
+    /// How clients of this server authenticate.
+    #[serde(default)]
+    pub auth: AuthConfig,
+    /// Where actions performed through the API are recorded.
+    #[serde(default)]
+    pub audit: AuditConfig,
+    /// What is logged about management API calls.
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// Safeguards around purging queues.
+    #[serde(default)]
+    pub purge: PurgeConfig,
+    /// Limits on importing messages.
+    #[serde(default)]
+    pub import: ImportConfig,
+    /// Forward each user's own RabbitMQ credentials (from their session) instead of the configured ones.
+    #[serde(default)]
+    pub credential_passthrough: bool,
+    /// Directory the React client is served from.
+    #[serde(default = "default_static_dir")]
+    pub static_dir: PathBuf,
+    /// Address the server binds to.
+    #[serde(default = "default_address")]
+    pub address: IpAddr,
+    /// Port the server listens on.
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+/// Id of the cluster configured through the `management` table.
+pub const DEFAULT_CLUSTER_ID: &str = "default";
+
+/// A named RabbitMQ cluster.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ClusterConfig {
+    /// Identifier used in URLs, e.g. `prod`. Letters, digits, `-` and `_` only.
+    pub id: String,
+    /// Human readable name, e.g. `Production`.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Connection settings for the cluster's management API.
+    #[serde(flatten)]
+    pub management: ManagementConfig,
+}
+
+/// Connection settings for the RabbitMQ management API.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ManagementConfig {
+    /// Root URL of the management API, e.g. `http://localhost:15672`.
+    pub root: String,
+    /// Username used to authenticate against the management API.
+    pub username: String,
+    /// Password used to authenticate against the management API.
+    pub password: String,
+    /// Timeout for a whole request, in seconds.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Timeout for establishing a connection, in seconds.
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// TLS settings used when the root URL is `https`.
+    #[serde(default)]
+    pub tls: TlsConfig,
+}
+
+/// TLS settings for the connection to the management API.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub struct TlsConfig {
+    /// PEM bundle of CA certificates to trust instead of the system ones.
+    pub ca_certificate: Option<PathBuf>,
+    /// PEM client certificate, for brokers requiring mutual TLS.
+    pub client_certificate: Option<PathBuf>,
+    /// PEM private key of the client certificate.
+    pub client_key: Option<PathBuf>,
+    /// Accept invalid or self-signed certificates. Never enable this in production.
+    pub accept_invalid_certs: bool,
+}
+
+/// Authentication of the clients of this server.
+///
+/// Authentication is enabled as soon as API tokens or a users file are configured; without either the
+/// API is open to anyone who can reach it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AuthConfig {
+    /// Static tokens accepted as `Authorization: Bearer <token>`, typically for scripts.
+    #[serde(default)]
+    pub api_tokens: Vec<ApiTokenConfig>,
+    /// htpasswd-style file of `username:bcrypt-hash` lines, used for HTTP basic and session login.
+    #[serde(default)]
+    pub users_file: Option<PathBuf>,
+    /// Lifetime of a session created by logging in, in seconds.
+    #[serde(default = "default_session_ttl_secs")]
+    pub session_ttl_secs: u64,
+    /// Role of every principal on every cluster and vhost, before `grants` are applied. `"none"`
+    /// denies access to anything that is not granted explicitly.
+    #[serde(
+        default = "default_role",
+        deserialize_with = "optional_role",
+        serialize_with = "serialize_optional_role"
+    )]
+    pub default_role: Option<Role>,
+    /// Roles granted to principals on matching clusters and vhosts.
+    #[serde(default)]
+    pub grants: Vec<RoleGrant>,
+}
+
+/// Grants a role to the principals matching `principal` on the clusters and vhosts matching the
+/// `cluster` and `vhost` glob patterns.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct RoleGrant {
+    /// Glob pattern of principal names, e.g. `alice` or `*`.
+    pub principal: String,
+    /// Glob pattern of cluster ids.
+    #[serde(default = "match_all")]
+    pub cluster: String,
+    /// Glob pattern of vhost names, e.g. `orders-*`.
+    #[serde(default = "match_all")]
+    pub vhost: String,
+    /// The granted role.
+    pub role: Role,
+}
+
+/// A static API token.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ApiTokenConfig {
+    /// Name of the principal the token authenticates as, e.g. `ci`.
+    pub name: String,
+    /// The secret token itself.
+    pub token: String,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            api_tokens: vec![],
+            users_file: None,
+            session_ttl_secs: default_session_ttl_secs(),
+            default_role: default_role(),
+            grants: vec![],
+        }
+    }
+}
+
+impl AuthConfig {
+    /// Indicates if clients have to authenticate.
+    pub fn is_enabled(&self) -> bool {
+        !self.api_tokens.is_empty() || self.users_file.is_some()
+    }
+
+    /// Lifetime of a session created by logging in.
+    pub fn session_ttl(&self) -> Duration {
+        Duration::from_secs(self.session_ttl_secs)
+    }
+
+    /// Validates the settings.
+    fn validate(&self) -> Result<(), ServerError> {
+        let invalid = |reason: String| Err(ServerError::Configuration { reason });
+
+        let mut names = HashSet::new();
+        for token in &self.api_tokens {
+            if token.name.is_empty() || token.token.is_empty() {
+                return invalid("auth.api_tokens entries need a name and a token".to_string());
+            }
+            if !names.insert(token.name.as_str()) {
+                return invalid(format!("API token name {:?} is used twice", token.name));
+            }
+        }
+        if let Some(users_file) = &self.users_file {
+            if !users_file.is_file() {
+                return invalid(format!(
+                    "auth.users_file {} does not exist",
+                    users_file.display()
+                ));
+            }
+        }
+        if self.session_ttl_secs == 0 {
+            return invalid("auth.session_ttl_secs must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ApiTokenConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiTokenConfig")
+            .field("name", &self.name)
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Settings of the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct AuditConfig {
+    /// JSON-lines file actions are appended to. Auditing is disabled when unset.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Size in bytes after which the file is rotated.
+    #[serde(default = "default_audit_max_bytes")]
+    pub max_bytes: u64,
+    /// Number of rotated files kept.
+    #[serde(default = "default_audit_max_files")]
+    pub max_files: usize,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        AuditConfig {
+            path: None,
+            max_bytes: default_audit_max_bytes(),
+            max_files: default_audit_max_files(),
+        }
+    }
+}
+
+/// Safeguards around purging queues.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct PurgeConfig {
+    /// Directory snapshots of queues are written to before they are purged. Snapshots are disabled
+    /// when unset.
+    #[serde(default)]
+    pub snapshot_dir: Option<PathBuf>,
+    /// Maximum number of messages a snapshot may hold; larger queues cannot be purged with a snapshot.
+    #[serde(default = "default_max_snapshot_messages")]
+    pub max_snapshot_messages: u64,
+    /// Seconds a purge confirmation token stays valid.
+    #[serde(default = "default_purge_token_ttl_secs")]
+    pub token_ttl_secs: u64,
+}
+
+impl Default for PurgeConfig {
+    fn default() -> Self {
+        PurgeConfig {
+            snapshot_dir: None,
+            max_snapshot_messages: default_max_snapshot_messages(),
+            token_ttl_secs: default_purge_token_ttl_secs(),
+        }
+    }
+}
+
+impl PurgeConfig {
+    /// How long a purge confirmation token stays valid.
+    pub fn token_ttl(&self) -> Duration {
+        Duration::from_secs(self.token_ttl_secs)
+    }
+
+    /// Validates the settings.
+    fn validate(&self) -> Result<(), ServerError> {
+        let invalid = |reason: String| Err(ServerError::Configuration { reason });
+
+        if let Some(snapshot_dir) = &self.snapshot_dir {
+            if !snapshot_dir.is_dir() {
+                return invalid(format!(
+                    "purge.snapshot_dir {} is not a directory",
+                    snapshot_dir.display()
+                ));
+            }
+        }
+        if self.max_snapshot_messages == 0 {
+            return invalid("purge.max_snapshot_messages must be at least 1".to_string());
+        }
+        if self.token_ttl_secs == 0 {
+            return invalid("purge.token_ttl_secs must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn default_max_snapshot_messages() -> u64 {
+    10_000
+}
+
+fn default_purge_token_ttl_secs() -> u64 {
+    5 * 60
+}
+
+/// Limits on importing messages from NDJSON files.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportConfig {
+    /// Maximum size in bytes of an uploaded file.
+    #[serde(default = "default_import_max_bytes")]
+    pub max_bytes: u64,
+    /// Seconds the ids of imported messages are remembered, so that importing a file again skips them.
+    #[serde(default = "default_import_ledger_ttl_secs")]
+    pub ledger_ttl_secs: u64,
+    /// JSON-lines file the ids of imported messages are kept in, so that they are remembered across
+    /// restarts. They are only kept in memory when unset.
+    #[serde(default)]
+    pub ledger_path: Option<PathBuf>,
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        ImportConfig {
+            max_bytes: default_import_max_bytes(),
+            ledger_ttl_secs: default_import_ledger_ttl_secs(),
+            ledger_path: None,
+        }
+    }
+}
+
+impl ImportConfig {
+    /// How long the ids of imported messages are remembered.
+    pub fn ledger_ttl(&self) -> Duration {
+        Duration::from_secs(self.ledger_ttl_secs)
+    }
+
+    /// Validates the settings.
+    fn validate(&self) -> Result<(), ServerError> {
+        let invalid = |reason: String| Err(ServerError::Configuration { reason });
+
+        if self.max_bytes == 0 {
+            return invalid("import.max_bytes must be at least 1".to_string());
+        }
+        if self.ledger_ttl_secs == 0 {
+            return invalid("import.ledger_ttl_secs must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn default_import_max_bytes() -> u64 {
+    64 * 1024 * 1024
+}
+
+fn default_import_ledger_ttl_secs() -> u64 {
+    24 * 60 * 60
+}
+
+/// Settings of the logs.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct LoggingConfig {
+    /// Maximum level logged (`off`, `error`, `warn`, `info`, `debug` or `trace`), overriding Rocket's
+    /// `log_level`. Request headers and bodies of management API calls are logged at `debug`.
+    #[serde(default)]
+    pub level: Option<log::LevelFilter>,
+    /// Fields of JSON request bodies whose values are replaced by `<redacted>` in the logs.
+    #[serde(default = "default_redact_fields")]
+    pub redact_fields: Vec<String>,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: None,
+            redact_fields: default_redact_fields(),
+        }
+    }
+}
+
+fn default_redact_fields() -> Vec<String> {
+    vec!["password".to_string()]
+}
+
+fn default_audit_max_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_audit_max_files() -> usize {
+    5
+}
+
+fn default_session_ttl_secs() -> u64 {
+    8 * 60 * 60
+}
+
+fn default_role() -> Option<Role> {
+    Some(Role::Viewer)
+}
+
+fn match_all() -> String {
+    "*".to_string()
+}
+
+/// Deserializes a role, or `None` from `"none"`.
+fn optional_role<'de, D>(deserializer: D) -> Result<Option<Role>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match String::deserialize(deserializer)?.as_str() {
+        "none" => Ok(None),
+        role => Role::deserialize(role.into_deserializer()).map(Some),
+    }
+}
+
+fn serialize_optional_role<S>(role: &Option<Role>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match role {
+        Some(role) => role.serialize(serializer),
+        None => serializer.serialize_str("none"),
+    }
+}
+
+fn default_static_dir() -> PathBuf {
+    PathBuf::from("./static")
+}
+
+fn default_address() -> IpAddr {
+    rocket::Config::default().address
+}
+
+fn default_port() -> u16 {
+    rocket::Config::default().port
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_TIMEOUT.as_secs()
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    DEFAULT_CONNECT_TIMEOUT.as_secs()
+}
+
+impl AppConfig {
+    /// Builds the figment the configuration (and Rocket itself) is read from.
+    ///
+    /// `.env` is loaded into the process environment first, then the management API settings in the
+    /// `RABBITMQ_MANAGEMENT_*` variables are merged over Rocket's own sources, e.g.
+    /// `RABBITMQ_MANAGEMENT_TIMEOUT_SECS` sets `management.timeout_secs`.
+    pub fn figment() -> Figment {
+        dotenv::dotenv().ok();
+        rocket::Config::figment().merge(
+            Env::prefixed(RABBITMQ_MANAGEMENT_ENV_PREFIX)
+                .map(|key| format!("management.{}", key).into())
+                .global(),
+        )
+    }
+
+    /// Extracts and validates the configuration from a figment.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Configuration` describing the first missing or invalid value.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::config::AppConfig;
+    /// use rocket::figment::{providers::Serialized, Figment};
+    ///
+    /// let figment = Figment::new().merge(Serialized::default(
+    ///     "management",
+    ///     serde_json::json!({"root": "http://localhost:15672", "username": "guest", "password": "guest"}),
+    /// ));
+    /// let config = AppConfig::from_figment(&figment).unwrap();
+    /// assert_eq!(config.all_clusters()[0].id, "default");
+    /// assert_eq!(config.all_clusters()[0].management.timeout_secs, 30);
+    /// ```
+    pub fn from_figment(figment: &Figment) -> Result<AppConfig, ServerError> {
+        let config: AppConfig = figment.extract().map_err(|e| ServerError::Configuration {
+            reason: e.to_string(),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// All configured clusters, starting with the one from the `management` table if it is set.
+    pub fn all_clusters(&self) -> Vec<ClusterConfig> {
+        let default = self.management.as_ref().map(|management| ClusterConfig {
+            id: DEFAULT_CLUSTER_ID.to_string(),
+            name: None,
+            management: management.clone(),
+        });
+        default
+            .into_iter()
+            .chain(self.clusters.iter().cloned())
+            .collect()
+    }
+
+    /// Checks the values that cannot be validated by deserialization alone.
+    pub fn validate(&self) -> Result<(), ServerError> {
+        let invalid = |reason: String| Err(ServerError::Configuration { reason });
+
+        let clusters = self.all_clusters();
+        if clusters.is_empty() {
+            return invalid(
+                "no RabbitMQ cluster configured: set management or clusters".to_string(),
+            );
+        }
+        let mut ids = HashSet::new();
+        for cluster in &clusters {
+            if cluster.id.is_empty()
+                || !cluster
+                    .id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return invalid(format!(
+                    "cluster id {:?} must only contain letters, digits, '-' and '_'",
+                    cluster.id
+                ));
+            }
+            if !ids.insert(cluster.id.as_str()) {
+                return invalid(format!("cluster id {:?} is used twice", cluster.id));
+            }
+            cluster
+                .management
+                .validate(&format!("clusters.{}", cluster.id))?;
+        }
+        self.auth.validate()?;
+        self.purge.validate()?;
+        self.import.validate()?;
+        if self.audit.max_bytes == 0 {
+            return invalid("audit.max_bytes must be at least 1".to_string());
+        }
+        if !self.auth.is_enabled() {
+            log::warn!("no API tokens or users file configured; the API is unauthenticated");
+        }
+        if !self.static_dir.is_dir() {
+            log::warn!(
+                "static_dir {} does not exist; the React client will not be served",
+                self.static_dir.display()
+            );
+        }
+        Ok(())
+    }
+}
+
+impl ManagementConfig {
+    /// Creates a configuration with the default timeouts and TLS settings.
+    pub fn new(root: &str, username: &str, password: &str) -> ManagementConfig {
+        ManagementConfig {
+            root: root.to_string(),
+            username: username.to_string(),
+            password: password.to_string(),
+            timeout_secs: default_timeout_secs(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            tls: TlsConfig::default(),
+        }
+    }
+
+    /// Timeout for a whole request.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Timeout for establishing a connection.
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs)
+    }
+
+    /// Validates the settings, naming them after `section` in error messages.
+    fn validate(&self, section: &str) -> Result<(), ServerError> {
+        let invalid = |reason: String| Err(ServerError::Configuration { reason });
+
+        match self.root.parse::<Uri>() {
+            Ok(uri) if matches!(uri.scheme_str(), Some("http") | Some("https")) => {}
+            _ => {
+                return invalid(format!(
+                    "{}.root must be an http(s) URL, got {:?}",
+                    section, self.root
+                ))
+            }
+        }
+        if self.username.is_empty() {
+            return invalid(format!("{}.username must not be empty", section));
+        }
+        if self.timeout_secs == 0 || self.connect_timeout_secs == 0 {
+            return invalid(format!("{} timeouts must be at least 1 second", section));
+        }
+        if self.tls.client_certificate.is_some() != self.tls.client_key.is_some() {
+            return invalid(format!(
+                "{0}.tls.client_certificate and {0}.tls.client_key must be set together",
+                section
+            ));
+        }
+        for path in [
+            &self.tls.ca_certificate,
+            &self.tls.client_certificate,
+            &self.tls.client_key,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if !path.is_file() {
+                return invalid(format!("TLS file {} does not exist", path.display()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ManagementConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManagementConfig")
+            .field("root", &self.root)
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .field("timeout_secs", &self.timeout_secs)
+            .field("connect_timeout_secs", &self.connect_timeout_secs)
+            .field("tls", &self.tls)
+            .finish()
+    }
+}
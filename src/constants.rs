@@ -1,3 +1,2 @@
-pub const RABBITMQ_MANAGEMENT_ROOT: &str = "RABBITMQ_MANAGEMENT_ROOT";
-pub const RABBITMQ_MANAGEMENT_PASSWORD: &str = "RABBITMQ_MANAGEMENT_PASSWORD";
-pub const RABBITMQ_MANAGEMENT_USERNAME: &str = "RABBITMQ_MANAGEMENT_USERNAME";
+/// Prefix of the environment variables holding the management API settings, e.g. `RABBITMQ_MANAGEMENT_ROOT`.
+pub const RABBITMQ_MANAGEMENT_ENV_PREFIX: &str = "RABBITMQ_MANAGEMENT_";
@@ -1,20 +1,386 @@
 use core::fmt;
 
-/// Common struct to denote server errors
+use rocket::{
+    http::Status,
+    response::{self, Responder},
+    serde::json::Json,
+    Request,
+};
+use serde::Serialize;
+
+use crate::logging::{next_request_id, RequestId};
+
+/// Maximum number of characters of an upstream response body kept in an error.
+pub const BODY_SNIPPET_LIMIT: usize = 512;
+
+/// Request-local flag telling whether the management API calls of a request are made with the
+/// credentials of its user (credential pass-through), see [`ServerError::status_for`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserCredentials(pub bool);
+
+/// Common enum to denote server errors
+///
+/// Variants describing a failed request carry the URI that was being requested so that callers
+/// (and logs) can tell which management API call failed. The URI and the response body are only
+/// logged: clients get the [`ServerError::public_message`].
 #[derive(Debug, Clone)]
-pub struct ServerError {
-    /// Custom message returned with the error
-    pub message: String,
+pub enum ServerError {
+    /// The application is missing or has invalid configuration.
+    Configuration {
+        /// Description of the configuration problem
+        reason: String,
+    },
+    /// The request sent to this server was rejected before reaching the management API.
+    InvalidRequest {
+        /// Reason the request was rejected
+        reason: String,
+    },
+    /// One or more request parameters failed validation.
+    InvalidParameters {
+        /// Validation errors, one per offending field
+        errors: Vec<FieldError>,
+    },
+    /// The request named a cluster that is not configured.
+    UnknownCluster {
+        /// Id of the requested cluster
+        id: String,
+    },
+    /// The client of this server did not authenticate, or its credentials were rejected.
+    Unauthenticated {
+        /// Reason authentication failed
+        reason: String,
+    },
+    /// The authenticated client does not have the role the request needs.
+    Forbidden {
+        /// What the client was not allowed to do
+        reason: String,
+    },
+    /// The request needs the user's own credentials for the cluster, and there is no session.
+    LoginRequired {
+        /// Id of the cluster to log in to
+        cluster: String,
+    },
+    /// The operation was stopped because the broker's state did not allow it to go on safely.
+    Conflict {
+        /// Why the operation was stopped, and what state it left things in
+        reason: String,
+    },
+    /// The request URI could not be built or parsed.
+    InvalidUri {
+        /// URI that was rejected
+        uri: String,
+        /// Reason the URI was rejected
+        reason: String,
+    },
+    /// The request did not complete within the configured timeout.
+    Timeout {
+        /// URI that timed out
+        uri: String,
+    },
+    /// The request failed before an HTTP response was received (connection refused, TLS, DNS, ...).
+    Transport {
+        /// URI that was requested
+        uri: String,
+        /// Description of the transport failure
+        reason: String,
+    },
+    /// The management API answered with a non-2xx status code.
+    UpstreamStatus {
+        /// URI that was requested
+        uri: String,
+        /// HTTP status code returned by the management API
+        status: u16,
+        /// Body returned by the management API, truncated to [`BODY_SNIPPET_LIMIT`] characters
+        body: String,
+    },
+    /// The request body could not be serialized.
+    Serialization {
+        /// Description of the serialization failure
+        reason: String,
+    },
+    /// The response body could not be deserialized into the expected type.
+    Deserialization {
+        /// URI that was requested
+        uri: String,
+        /// Description of the deserialization failure
+        reason: String,
+        /// Raw response body, truncated to [`BODY_SNIPPET_LIMIT`] characters
+        body: String,
+    },
 }
 
 impl ServerError {
-    pub fn new(message: String) -> ServerError {
-        ServerError { message }
+    /// Builds a [`ServerError`] from an `isahc` error, separating timeouts from other transport failures.
+    pub fn from_transport(uri: &str, error: isahc::Error) -> ServerError {
+        if error.is_timeout() {
+            ServerError::Timeout {
+                uri: uri.to_string(),
+            }
+        } else {
+            ServerError::Transport {
+                uri: uri.to_string(),
+                reason: error.to_string(),
+            }
+        }
+    }
+
+    /// Returns the upstream HTTP status code, if the management API returned one.
+    pub fn upstream_status(&self) -> Option<u16> {
+        match self {
+            ServerError::UpstreamStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// Indicates if the management API rejected the credentials (401).
+    pub fn is_unauthorized(&self) -> bool {
+        self.upstream_status() == Some(401)
+    }
+
+    /// Indicates if the management API could not find the requested resource (404).
+    pub fn is_not_found(&self) -> bool {
+        self.upstream_status() == Some(404)
+    }
+
+    /// Indicates if the request timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, ServerError::Timeout { .. })
+    }
+
+    /// HTTP status returned to our own clients for this error.
+    ///
+    /// Errors the caller can act on (bad request, missing vhost or queue) keep the management API's
+    /// status; broker-side failures become gateway errors. The management API rejecting the configured
+    /// credentials (401 or 403) is a failure of this server, not of its client, so it is a gateway
+    /// error too, whose [`ErrorBody::upstream_status`] tells what happened.
+    pub fn status(&self) -> Status {
+        self.status_for(false)
+    }
+
+    /// HTTP status returned for this error to a client whose own credentials were used for the
+    /// management API if `user_credentials` is set: their rejection (401 or 403) is then returned as
+    /// is, so that the client can log in again.
+    pub fn status_for(&self, user_credentials: bool) -> Status {
+        match self {
+            ServerError::Configuration { .. } | ServerError::Serialization { .. } => {
+                Status::InternalServerError
+            }
+            ServerError::InvalidRequest { .. } | ServerError::InvalidUri { .. } => {
+                Status::BadRequest
+            }
+            ServerError::InvalidParameters { .. } => Status::UnprocessableEntity,
+            ServerError::UnknownCluster { .. } => Status::NotFound,
+            ServerError::Forbidden { .. } => Status::Forbidden,
+            ServerError::Conflict { .. } => Status::Conflict,
+            ServerError::Unauthenticated { .. } | ServerError::LoginRequired { .. } => {
+                Status::Unauthorized
+            }
+            ServerError::Timeout { .. } => Status::GatewayTimeout,
+            ServerError::Transport { .. } => Status::ServiceUnavailable,
+            ServerError::UpstreamStatus { status, .. } => match status {
+                400 => Status::BadRequest,
+                401 if user_credentials => Status::Unauthorized,
+                403 if user_credentials => Status::Forbidden,
+                404 => Status::NotFound,
+                _ => Status::BadGateway,
+            },
+            ServerError::Deserialization { .. } => Status::BadGateway,
+        }
+    }
+
+    /// Description of the error returned to clients.
+    ///
+    /// Unlike the [`fmt::Display`] output, which is logged, it leaves out the management API URI and
+    /// response body.
+    pub fn public_message(&self) -> String {
+        match self {
+            ServerError::InvalidUri { reason, .. } => format!("Invalid URI: {}", reason),
+            ServerError::Timeout { .. } => "The management API did not answer in time".to_string(),
+            ServerError::Transport { .. } => "The management API could not be reached".to_string(),
+            ServerError::UpstreamStatus { status, .. } => {
+                format!("The management API answered with HTTP {}", status)
+            }
+            ServerError::Deserialization { .. } => {
+                "The management API answered with an unexpected response".to_string()
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Logs the error with the id of the request it failed, where its URI and body can be found.
+    pub fn log(&self, request_id: &str) {
+        log::error!("request_id={} {}", request_id, self);
+    }
+
+    /// Short machine-readable code identifying the kind of error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ServerError::Configuration { .. } => "configuration_error",
+            ServerError::InvalidRequest { .. } => "invalid_request",
+            ServerError::InvalidParameters { .. } => "invalid_parameters",
+            ServerError::UnknownCluster { .. } => "unknown_cluster",
+            ServerError::Unauthenticated { .. } => "unauthenticated",
+            ServerError::Forbidden { .. } => "forbidden",
+            ServerError::LoginRequired { .. } => "login_required",
+            ServerError::Conflict { .. } => "conflict",
+            ServerError::InvalidUri { .. } => "invalid_uri",
+            ServerError::Timeout { .. } => "upstream_timeout",
+            ServerError::Transport { .. } => "upstream_unreachable",
+            ServerError::UpstreamStatus { status, .. } => match status {
+                400 => "upstream_bad_request",
+                401 => "upstream_unauthorized",
+                403 => "upstream_forbidden",
+                404 => "upstream_not_found",
+                _ => "upstream_error",
+            },
+            ServerError::Serialization { .. } => "serialization_error",
+            ServerError::Deserialization { .. } => "upstream_invalid_response",
+        }
+    }
+}
+
+/// JSON body returned by the API whenever a request fails.
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct ErrorBody {
+    /// Short machine-readable code identifying the kind of error
+    pub code: String,
+    /// Human readable description of the error
+    pub message: String,
+    /// HTTP status returned by the management API, if the error came from it
+    pub upstream_status: Option<u16>,
+    /// Field-level validation errors, if the request parameters were invalid
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+/// Validation error for a single request parameter.
+#[derive(Serialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct FieldError {
+    /// Name of the offending field
+    pub field: String,
+    /// Description of what is wrong with it
+    pub message: String,
+}
+
+impl FieldError {
+    /// Converts the errors collected by Rocket while parsing a form or query string.
+    pub fn from_form_errors(errors: &rocket::form::Errors<'_>) -> Vec<FieldError> {
+        errors
+            .iter()
+            .map(|error| FieldError {
+                field: error
+                    .name
+                    .as_ref()
+                    .map(|name| name.to_string())
+                    .unwrap_or_default(),
+                message: error.kind.to_string(),
+            })
+            .collect()
+    }
+}
+
+impl ErrorBody {
+    /// Creates an error body that did not originate from the management API.
+    pub fn new(code: &str, message: String) -> ErrorBody {
+        ErrorBody {
+            code: code.to_string(),
+            message,
+            upstream_status: None,
+            errors: vec![],
+        }
+    }
+}
+
+impl From<&ServerError> for ErrorBody {
+    fn from(error: &ServerError) -> Self {
+        ErrorBody {
+            code: error.code().to_string(),
+            message: error.public_message(),
+            upstream_status: error.upstream_status(),
+            errors: match error {
+                ServerError::InvalidParameters { errors } => errors.clone(),
+                _ => vec![],
+            },
+        }
+    }
+}
+
+impl<'r> Responder<'r, 'static> for ServerError {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        self.log(&request.local_cache(|| RequestId(next_request_id())).0);
+        // Kept for the fairings (e.g. the audit log) that run once the response is built.
+        request.local_cache(|| Some(self.clone()));
+        let status = self.status_for(request.local_cache(UserCredentials::default).0);
+        (status, Json(ErrorBody::from(&self))).respond_to(request)
+    }
+}
+
+/// Truncates a response body to at most [`BODY_SNIPPET_LIMIT`] characters.
+///
+/// # Example
+///
+/// ```rust
+/// use rabbitmq_messages_management::exceptions::{snippet, BODY_SNIPPET_LIMIT};
+/// let body = "a".repeat(BODY_SNIPPET_LIMIT + 10);
+/// assert_eq!(snippet(&body).chars().count(), BODY_SNIPPET_LIMIT + 3);
+/// assert_eq!(snippet("short"), "short");
+/// ```
+pub fn snippet(body: &str) -> String {
+    match body.char_indices().nth(BODY_SNIPPET_LIMIT) {
+        Some((index, _)) => format!("{}...", &body[..index]),
+        None => body.to_string(),
     }
 }
 
 impl fmt::Display for ServerError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.message)
+        match self {
+            ServerError::Configuration { reason } => write!(f, "Configuration error: {}", reason),
+            ServerError::InvalidRequest { reason } => write!(f, "Invalid request: {}", reason),
+            ServerError::InvalidParameters { errors } => {
+                let fields: Vec<String> = errors
+                    .iter()
+                    .map(|error| format!("{}: {}", error.field, error.message))
+                    .collect();
+                write!(f, "Invalid parameters: {}", fields.join("; "))
+            }
+            ServerError::UnknownCluster { id } => write!(f, "Unknown cluster {:?}", id),
+            ServerError::Unauthenticated { reason } => {
+                write!(f, "Authentication required: {}", reason)
+            }
+            ServerError::Forbidden { reason } => write!(f, "Forbidden: {}", reason),
+            ServerError::LoginRequired { cluster } => {
+                write!(
+                    f,
+                    "Log in to cluster {:?} with your RabbitMQ credentials",
+                    cluster
+                )
+            }
+            ServerError::Conflict { reason } => write!(f, "Conflict: {}", reason),
+            ServerError::InvalidUri { uri, reason } => {
+                write!(f, "Invalid URI {}: {}", uri, reason)
+            }
+            ServerError::Timeout { uri } => write!(f, "Request to {} timed out", uri),
+            ServerError::Transport { uri, reason } => {
+                write!(f, "Request to {} failed: {}", uri, reason)
+            }
+            ServerError::UpstreamStatus { uri, status, body } => {
+                write!(f, "{} returned HTTP {}: {}", uri, status, body)
+            }
+            ServerError::Serialization { reason } => {
+                write!(f, "Failed to serialize request body: {}", reason)
+            }
+            ServerError::Deserialization { uri, reason, body } => {
+                write!(
+                    f,
+                    "Failed to deserialize response from {}: {} (body: {})",
+                    uri, reason, body
+                )
+            }
+        }
     }
 }
+
+impl std::error::Error for ServerError {}
@@ -0,0 +1,385 @@
+use std::{io::Write, mem};
+
+use rocket::FromFormField;
+use serde_json::Value;
+use serde_json_path::JsonPath;
+use time::OffsetDateTime;
+
+use crate::{
+    exceptions::{FieldError, ServerError},
+    purge::file_name_part,
+    rabbitmq::queues::ResponseForQueryingMessages,
+};
+
+/// Name of the file an export of a queue is downloaded as, e.g. `local-orders-dlq-1727776800000.csv`.
+pub fn export_file_name(cluster: &str, vhost: &str, queue: &str, format: ExportFormat) -> String {
+    format!(
+        "{}-{}-{}-{}.{}",
+        file_name_part(cluster),
+        file_name_part(vhost),
+        file_name_part(queue),
+        OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000,
+        format.extension()
+    )
+}
+
+/// Columns of a CSV export when none are requested.
+pub const DEFAULT_CSV_COLUMNS: [&str; 9] = [
+    "exchange",
+    "routing_key",
+    "redelivered",
+    "properties.message_id",
+    "properties.content_type",
+    "properties.timestamp",
+    "headers",
+    "payload_encoding",
+    "payload",
+];
+
+/// Compression level of `tar.zst` exports.
+const ZSTD_LEVEL: i32 = 3;
+
+/// Format messages are exported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField)]
+pub enum ExportFormat {
+    /// One message per line, in the shape returned by the API (and accepted by the publish endpoint).
+    #[field(value = "ndjson")]
+    Ndjson,
+    /// One message per row, with the requested columns.
+    #[field(value = "csv")]
+    Csv,
+    /// A zstd-compressed tar archive holding, for each message, its raw payload and a JSON file with
+    /// the rest of the message.
+    #[field(value = "tar.zst")]
+    TarZstd,
+}
+
+impl ExportFormat {
+    /// MIME type of an export in this format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::TarZstd => "application/zstd",
+        }
+    }
+
+    /// File name extension of an export in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Csv => "csv",
+            ExportFormat::TarZstd => "tar.zst",
+        }
+    }
+}
+
+/// A column of a CSV export.
+#[derive(Debug, Clone)]
+pub enum ExportColumn {
+    /// `exchange`: exchange the message was published to.
+    Exchange,
+    /// `routing_key`: routing key the message was published with.
+    RoutingKey,
+    /// `redelivered`: whether the message was redelivered.
+    Redelivered,
+    /// `payload`: the payload, as returned by the API.
+    Payload,
+    /// `payload_encoding`: `string` or `base64`.
+    PayloadEncoding,
+    /// `payload_bytes`: size of the payload.
+    PayloadBytes,
+    /// `properties`: every property, as JSON.
+    Properties,
+    /// `properties.<name>`: a single property, e.g. `properties.message_id`.
+    Property(String),
+    /// `headers`: every header, as JSON.
+    Headers,
+    /// `headers.<name>`: a single header, e.g. `headers.x-tenant`.
+    Header(String),
+    /// `$...`: the nodes a JSONPath query selects in the (JSON) payload, decompressed if needed,
+    /// e.g. `$.order.id`.
+    JsonPath(JsonPath),
+}
+
+impl ExportColumn {
+    /// Parses a column from its name, as listed in [`ExportColumn`].
+    pub fn parse(name: &str) -> Result<ExportColumn, String> {
+        if name.starts_with('$') {
+            return JsonPath::parse(name)
+                .map(ExportColumn::JsonPath)
+                .map_err(|e| e.to_string());
+        }
+        if let Some(property) = name.strip_prefix("properties.") {
+            return Ok(ExportColumn::Property(property.to_string()));
+        }
+        if let Some(header) = name.strip_prefix("headers.") {
+            return Ok(ExportColumn::Header(header.to_string()));
+        }
+        match name {
+            "exchange" => Ok(ExportColumn::Exchange),
+            "routing_key" => Ok(ExportColumn::RoutingKey),
+            "redelivered" => Ok(ExportColumn::Redelivered),
+            "payload" => Ok(ExportColumn::Payload),
+            "payload_encoding" => Ok(ExportColumn::PayloadEncoding),
+            "payload_bytes" => Ok(ExportColumn::PayloadBytes),
+            "properties" => Ok(ExportColumn::Properties),
+            "headers" => Ok(ExportColumn::Headers),
+            _ => Err(format!("unknown column {:?}", name)),
+        }
+    }
+
+    /// Parses the columns of a CSV export, falling back to [`DEFAULT_CSV_COLUMNS`] if none are given.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidParameters` listing every column that could not be parsed.
+    pub fn parse_all(names: &[String]) -> Result<Vec<(String, ExportColumn)>, ServerError> {
+        let names = match names.is_empty() {
+            true => DEFAULT_CSV_COLUMNS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+            false => names.to_vec(),
+        };
+        let mut columns = vec![];
+        let mut errors = vec![];
+        for name in names {
+            match ExportColumn::parse(&name) {
+                Ok(column) => columns.push((name, column)),
+                Err(message) => errors.push(FieldError {
+                    field: "columns".to_string(),
+                    message,
+                }),
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(columns),
+            false => Err(ServerError::InvalidParameters { errors }),
+        }
+    }
+
+    /// Value of the column for a message, flattened to text: strings as they are, other JSON values
+    /// as JSON, and nothing for missing values.
+    fn cell(&self, message: &ResponseForQueryingMessages, properties: &Value) -> String {
+        let value = match self {
+            ExportColumn::Exchange => return message.exchange().to_string(),
+            ExportColumn::RoutingKey => return message.routing_key().to_string(),
+            ExportColumn::Redelivered => return message.redelivered().to_string(),
+            ExportColumn::Payload => return message.payload().to_string(),
+            ExportColumn::PayloadEncoding => return message.payload_encoding().to_string(),
+            ExportColumn::PayloadBytes => return message.payload_bytes().to_string(),
+            ExportColumn::Properties => properties.clone(),
+            ExportColumn::Property(name) => properties.get(name).cloned().unwrap_or_default(),
+            ExportColumn::Headers => properties.get("headers").cloned().unwrap_or_default(),
+            ExportColumn::Header(name) => properties
+                .get("headers")
+                .and_then(|headers| headers.get(name))
+                .cloned()
+                .unwrap_or_default(),
+            ExportColumn::JsonPath(path) => {
+                let payload = message
+                    .content()
+                    .and_then(|payload| serde_json::from_slice::<Value>(&payload).ok())
+                    .unwrap_or_default();
+                let nodes = path.query(&payload).all();
+                match nodes.as_slice() {
+                    [] => Value::Null,
+                    [node] => (*node).clone(),
+                    _ => Value::Array(nodes.into_iter().cloned().collect()),
+                }
+            }
+        };
+        match value {
+            Value::Null => String::new(),
+            Value::String(text) => text,
+            value => value.to_string(),
+        }
+    }
+}
+
+/// Encodes messages in an export format, a few at a time, so an export can be streamed without
+/// holding it in memory.
+///
+/// Messages are added with [`ExportEncoder::write`], and the bytes encoded so far are handed out by
+/// [`ExportEncoder::take_output`]; [`ExportEncoder::finish`] hands out the rest.
+pub struct ExportEncoder {
+    /// Encoder of the chosen format.
+    encoding: Encoding,
+    /// Number of messages written so far.
+    written: u64,
+}
+
+/// State of an [`ExportEncoder`], by format.
+enum Encoding {
+    /// JSON lines written so far.
+    Ndjson(Vec<u8>),
+    /// CSV rows written so far, with the columns to write.
+    Csv(Vec<u8>, Vec<ExportColumn>),
+    /// Tar archive, compressed as it is written.
+    TarZstd(tar::Builder<zstd::Encoder<'static, Vec<u8>>>),
+}
+
+impl ExportEncoder {
+    /// Starts an export. `columns` are only used by CSV exports, whose first row holds their names.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Serialization` if the export cannot be started.
+    pub fn new(
+        format: ExportFormat,
+        columns: Vec<(String, ExportColumn)>,
+    ) -> Result<ExportEncoder, ServerError> {
+        let encoding = match format {
+            ExportFormat::Ndjson => Encoding::Ndjson(vec![]),
+            ExportFormat::Csv => Encoding::Csv(
+                csv_row(columns.iter().map(|(name, _)| name.as_str()))?,
+                columns.into_iter().map(|(_, column)| column).collect(),
+            ),
+            ExportFormat::TarZstd => {
+                let encoder = zstd::Encoder::new(vec![], ZSTD_LEVEL).map_err(serialization)?;
+                Encoding::TarZstd(tar::Builder::new(encoder))
+            }
+        };
+        Ok(ExportEncoder {
+            encoding,
+            written: 0,
+        })
+    }
+
+    /// Adds a message to the export.
+    ///
+    /// In `tar.zst` exports, the message is stored as two files named after its position in the
+    /// export: `000001.message.json`, holding the message without its payload, and the payload itself,
+    /// decoded if it is base64 encoded, as `000001.payload.json`, `.txt` or `.bin` depending on its
+    /// content type.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Serialization` if the message cannot be encoded.
+    pub fn write(&mut self, message: &ResponseForQueryingMessages) -> Result<(), ServerError> {
+        self.written += 1;
+        match &mut self.encoding {
+            Encoding::Ndjson(output) => {
+                serde_json::to_writer(&mut *output, message).map_err(serialization)?;
+                output.push(b'\n');
+            }
+            Encoding::Csv(output, columns) => {
+                let properties =
+                    serde_json::to_value(message.properties()).map_err(serialization)?;
+                output.extend(csv_row(
+                    columns
+                        .iter()
+                        .map(|column| column.cell(message, &properties)),
+                )?);
+            }
+            Encoding::TarZstd(builder) => {
+                let name = format!("{:06}", self.written);
+                let payload = message
+                    .decoded_payload()
+                    .unwrap_or_else(|| message.payload().as_bytes().to_vec());
+                let payload_file = format!("{}.payload.{}", name, payload_extension(message));
+                let mut metadata = serde_json::to_value(message).map_err(serialization)?;
+                if let Some(fields) = metadata.as_object_mut() {
+                    fields.remove("payload");
+                    fields.remove("payload_encoding");
+                    fields.insert(
+                        "payload_file".to_string(),
+                        Value::from(payload_file.clone()),
+                    );
+                }
+                let metadata = serde_json::to_vec_pretty(&metadata).map_err(serialization)?;
+                append_file(builder, &format!("{}.message.json", name), &metadata)?;
+                append_file(builder, &payload_file, &payload)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of messages written so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Hands out the bytes encoded since the last call.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Serialization` if the pending bytes cannot be flushed.
+    pub fn take_output(&mut self) -> Result<Vec<u8>, ServerError> {
+        match &mut self.encoding {
+            Encoding::Ndjson(output) | Encoding::Csv(output, _) => Ok(mem::take(output)),
+            Encoding::TarZstd(builder) => {
+                let encoder = builder.get_mut();
+                encoder.flush().map_err(serialization)?;
+                Ok(mem::take(encoder.get_mut()))
+            }
+        }
+    }
+
+    /// Ends the export and hands out its last bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Serialization` if the export cannot be completed.
+    pub fn finish(self) -> Result<Vec<u8>, ServerError> {
+        match self.encoding {
+            Encoding::Ndjson(output) | Encoding::Csv(output, _) => Ok(output),
+            Encoding::TarZstd(builder) => builder
+                .into_inner()
+                .and_then(|encoder| encoder.finish())
+                .map_err(serialization),
+        }
+    }
+}
+
+/// Encodes a CSV row, quoting the fields that need it.
+fn csv_row<I>(fields: I) -> Result<Vec<u8>, ServerError>
+where
+    I: IntoIterator,
+    I::Item: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record(fields).map_err(serialization)?;
+    writer
+        .into_inner()
+        .map_err(|e| serialization(e.into_error()))
+}
+
+/// Appends a file to a tar archive.
+fn append_file<W: Write>(
+    builder: &mut tar::Builder<W>,
+    path: &str,
+    data: &[u8],
+) -> Result<(), ServerError> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(OffsetDateTime::now_utc().unix_timestamp().max(0) as u64);
+    builder
+        .append_data(&mut header, path, data)
+        .map_err(serialization)
+}
+
+/// Extension of the file holding the payload of a message, from its content type.
+fn payload_extension(message: &ResponseForQueryingMessages) -> &'static str {
+    let content_type = message
+        .properties()
+        .content_type()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if content_type.contains("json") {
+        "json"
+    } else if content_type.starts_with("text/") {
+        "txt"
+    } else {
+        "bin"
+    }
+}
+
+/// Reports an export that could not be encoded.
+fn serialization<E: ToString>(error: E) -> ServerError {
+    ServerError::Serialization {
+        reason: error.to_string(),
+    }
+}
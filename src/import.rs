@@ -0,0 +1,510 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use rocket::tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt},
+    time::{interval, MissedTickBehavior},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    client::ManagementClient,
+    config::ImportConfig,
+    exceptions::ServerError,
+    rabbitmq::{
+        exchanges::{MessageToPublish, PublishEncoding, DEFAULT_EXCHANGE},
+        moves::error_body,
+        queues::Properties,
+    },
+};
+
+/// Messages published per second when no rate is requested.
+pub const DEFAULT_IMPORT_RATE: u32 = 50;
+
+/// Maximum number of messages published per second.
+pub const MAX_IMPORT_RATE: u32 = 1000;
+
+/// Maximum number of lines listed in the `errors` and `preview` of a report.
+pub const MAX_REPORTED_LINES: usize = 1000;
+
+/// A line of an NDJSON import: a message in the shape returned by the API, e.g. a line of an export
+/// or of a purge snapshot. Other fields, such as `redelivered`, are ignored.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportRecord {
+    /// Exchange the message was originally published to; empty for the default exchange.
+    #[serde(default)]
+    pub exchange: String,
+    /// Routing key the message was originally published with.
+    #[serde(default)]
+    pub routing_key: String,
+    /// Payload of the message.
+    pub payload: String,
+    /// Encoding of the payload.
+    #[serde(default)]
+    pub payload_encoding: PublishEncoding,
+    /// Properties of the message, including its headers.
+    #[serde(default)]
+    pub properties: Properties,
+}
+
+/// Where imported messages are published. Unset fields are taken from each message.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ImportDestination {
+    /// Exchange to publish every message to, instead of its original exchange.
+    pub exchange: Option<String>,
+    /// Routing key to publish every message with, instead of its original routing key.
+    pub routing_key: Option<String>,
+}
+
+impl ImportDestination {
+    /// The exchange and routing key a record is published with.
+    ///
+    /// The default exchange (an empty name in exported messages) is returned as [`DEFAULT_EXCHANGE`].
+    pub fn route(&self, record: &ImportRecord) -> (String, String) {
+        let exchange = match (&self.exchange, record.exchange.as_str()) {
+            (Some(exchange), _) => exchange.clone(),
+            (None, "") => DEFAULT_EXCHANGE.to_string(),
+            (None, exchange) => exchange.to_string(),
+        };
+        let routing_key = self
+            .routing_key
+            .clone()
+            .unwrap_or_else(|| record.routing_key.clone());
+        (exchange, routing_key)
+    }
+}
+
+/// Where an import publishes: the scope in which message ids are remembered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImportTarget {
+    /// Cluster messages are published to.
+    pub cluster: String,
+    /// Vhost messages are published to.
+    pub vhost: String,
+    /// Exchange and routing key messages are published with.
+    pub destination: ImportDestination,
+}
+
+/// Options of an import.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Messages published per second, at most.
+    pub rate: u32,
+    /// Checks the file and reports what would be published, without publishing anything.
+    pub dry_run: bool,
+    /// Publishes messages even if a message with the same id was already imported.
+    pub force: bool,
+    /// Maximum size of the file, in bytes.
+    pub max_bytes: u64,
+}
+
+/// A line that was (or, in a dry run, would be) published.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportedLine {
+    /// Line number in the file, from 1.
+    pub line: usize,
+    /// Application message identifier, if set.
+    pub message_id: Option<String>,
+    /// Exchange the message is published to.
+    pub exchange: String,
+    /// Routing key the message is published with.
+    pub routing_key: String,
+}
+
+/// A line that could not be imported.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct LineError {
+    /// Line number in the file, from 1.
+    pub line: usize,
+    /// Application message identifier, if the line could be read.
+    pub message_id: Option<String>,
+    /// Why the line was not imported.
+    pub message: String,
+}
+
+/// Exact account of what an import did.
+#[derive(Serialize, Debug, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct ImportReport {
+    /// Whether nothing was published.
+    pub dry_run: bool,
+    /// Non-empty lines read.
+    pub lines: usize,
+    /// Messages published (or, in a dry run, that would be published).
+    pub published: usize,
+    /// Messages skipped because a message with the same id was already imported.
+    pub skipped: usize,
+    /// Lines that could not be imported.
+    pub failed: usize,
+    /// The first [`MAX_REPORTED_LINES`] lines that could not be imported.
+    pub errors: Vec<LineError>,
+    /// In a dry run, the first [`MAX_REPORTED_LINES`] messages that would be published.
+    pub preview: Vec<ImportedLine>,
+    /// Error the import stopped on, if it did not read the whole file. The line it stopped on was
+    /// not published.
+    #[serde(serialize_with = "error_body")]
+    pub error: Option<ServerError>,
+}
+
+impl ImportReport {
+    /// Records a line that could not be imported.
+    fn fail(&mut self, line: usize, message_id: Option<String>, message: String) {
+        self.failed += 1;
+        if self.errors.len() < MAX_REPORTED_LINES {
+            self.errors.push(LineError {
+                line,
+                message_id,
+                message,
+            });
+        }
+    }
+}
+
+/// Ids of the messages imported recently, by target, so that importing a file again (e.g. after a
+/// failure) does not publish its messages twice.
+///
+/// The ids of a target are kept for a while after the last import to it. They are kept in memory and,
+/// if the ledger was opened with a path, appended to a JSON-lines file by [`ImportLedger::flush`], from
+/// which they are loaded back when the server restarts.
+#[derive(Debug)]
+pub struct ImportLedger {
+    /// How long the ids of a target are kept after its last import.
+    ttl: Duration,
+    /// Ids imported to each target, with their expiry.
+    imported: Mutex<HashMap<ImportTarget, (HashSet<String>, Instant)>>,
+    /// File the ids are appended to, with its path, if the ledger is persisted.
+    file: Option<(PathBuf, Mutex<File>)>,
+    /// Ids recorded since the last flush, to append to the file.
+    pending: Mutex<Vec<LedgerEntry>>,
+}
+
+/// A line of the ledger file: a message imported to a target.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+struct LedgerEntry {
+    /// When the message was imported.
+    #[serde(with = "time::serde::rfc3339")]
+    time: OffsetDateTime,
+    /// Cluster the message was published to.
+    cluster: String,
+    /// Vhost the message was published to.
+    vhost: String,
+    /// Exchange the import published to, if it overrode the exchange of the messages.
+    exchange: Option<String>,
+    /// Routing key the import published with, if it overrode the routing key of the messages.
+    routing_key: Option<String>,
+    /// Application message identifier.
+    message_id: String,
+}
+
+impl LedgerEntry {
+    /// The target the message was imported to.
+    fn target(&self) -> ImportTarget {
+        ImportTarget {
+            cluster: self.cluster.clone(),
+            vhost: self.vhost.clone(),
+            destination: ImportDestination {
+                exchange: self.exchange.clone(),
+                routing_key: self.routing_key.clone(),
+            },
+        }
+    }
+}
+
+impl ImportLedger {
+    /// Creates an empty ledger keeping ids for `ttl`, in memory only.
+    pub fn new(ttl: Duration) -> ImportLedger {
+        ImportLedger {
+            ttl,
+            imported: Mutex::new(HashMap::new()),
+            file: None,
+            pending: Mutex::new(vec![]),
+        }
+    }
+
+    /// Opens the ledger at `config.ledger_path`, loading the ids that have not expired and rewriting
+    /// the file without the others, or creates an in-memory ledger if no path is set.
+    ///
+    /// Lines that cannot be parsed (e.g. truncated by a crash) are dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Configuration` if the file cannot be read or written.
+    pub fn open(config: &ImportConfig) -> Result<ImportLedger, ServerError> {
+        let ttl = config.ledger_ttl();
+        let Some(path) = &config.ledger_path else {
+            return Ok(ImportLedger::new(ttl));
+        };
+        let failed = |action: &str, e: io::Error| ServerError::Configuration {
+            reason: format!("cannot {} import ledger {}: {}", action, path.display(), e),
+        };
+
+        let mut entries = vec![];
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    let line = line.map_err(|e| failed("read", e))?;
+                    if let Ok(entry) = serde_json::from_str::<LedgerEntry>(&line) {
+                        entries.push(entry);
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(failed("read", e)),
+        }
+
+        // The ids of a target expire together, `ttl` after the last import to it.
+        let mut last_imports: HashMap<ImportTarget, OffsetDateTime> = HashMap::new();
+        for entry in &entries {
+            let last = last_imports.entry(entry.target()).or_insert(entry.time);
+            *last = (*last).max(entry.time);
+        }
+        let now = OffsetDateTime::now_utc();
+        let mut imported = HashMap::new();
+        let mut kept = String::new();
+        for entry in entries {
+            let target = entry.target();
+            let Ok(remaining) = Duration::try_from(last_imports[&target] + ttl - now) else {
+                continue;
+            };
+            let line = serde_json::to_string(&entry).map_err(|e| ServerError::Serialization {
+                reason: e.to_string(),
+            })?;
+            kept.push_str(&line);
+            kept.push('\n');
+            imported
+                .entry(target)
+                .or_insert_with(|| (HashSet::new(), Instant::now() + remaining))
+                .0
+                .insert(entry.message_id);
+        }
+
+        let mut compacted = path.clone().into_os_string();
+        compacted.push(".tmp");
+        fs::write(&compacted, kept)
+            .and_then(|_| fs::rename(&compacted, path))
+            .map_err(|e| failed("write", e))?;
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map_err(|e| failed("open", e))?;
+        Ok(ImportLedger {
+            ttl,
+            imported: Mutex::new(imported),
+            file: Some((path.clone(), Mutex::new(file))),
+            pending: Mutex::new(vec![]),
+        })
+    }
+
+    /// Indicates if a message with this id was imported to `target`.
+    pub fn contains(&self, target: &ImportTarget, message_id: &str) -> bool {
+        let imported = self.imported.lock().unwrap_or_else(|e| e.into_inner());
+        imported
+            .get(target)
+            .is_some_and(|(ids, expires)| *expires > Instant::now() && ids.contains(message_id))
+    }
+
+    /// Remembers that a message with this id was imported to `target`.
+    ///
+    /// The id is only kept in memory until [`ImportLedger::flush`] writes it to the ledger file.
+    pub fn record(&self, target: &ImportTarget, message_id: &str) {
+        let now = Instant::now();
+        {
+            let mut imported = self.imported.lock().unwrap_or_else(|e| e.into_inner());
+            imported.retain(|_, (_, expires)| *expires > now);
+            let (ids, expires) = imported
+                .entry(target.clone())
+                .or_insert_with(|| (HashSet::new(), now));
+            ids.insert(message_id.to_string());
+            *expires = now + self.ttl;
+        }
+
+        if self.file.is_some() {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            pending.push(LedgerEntry {
+                time: OffsetDateTime::now_utc(),
+                cluster: target.cluster.clone(),
+                vhost: target.vhost.clone(),
+                exchange: target.destination.exchange.clone(),
+                routing_key: target.destination.routing_key.clone(),
+                message_id: message_id.to_string(),
+            });
+        }
+    }
+
+    /// Appends the ids recorded since the last flush to the ledger file, if any, in one write.
+    ///
+    /// This writes to a file, so it blocks: call it from `block_in_place` or `spawn_blocking` in async
+    /// code.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Configuration` if the ids cannot be written to the ledger file. They are
+    /// remembered until the server stops all the same.
+    pub fn flush(&self) -> Result<(), ServerError> {
+        let Some((path, file)) = &self.file else {
+            return Ok(());
+        };
+        let entries = std::mem::take(&mut *self.pending.lock().unwrap_or_else(|e| e.into_inner()));
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for entry in &entries {
+            let line = serde_json::to_string(entry).map_err(|e| ServerError::Serialization {
+                reason: e.to_string(),
+            })?;
+            lines.push_str(&line);
+            lines.push('\n');
+        }
+        let mut file = file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(lines.as_bytes())
+            .and_then(|_| file.flush())
+            .map_err(|e| ServerError::Configuration {
+                reason: format!("cannot write import ledger {}: {}", path.display(), e),
+            })
+    }
+}
+
+impl ManagementClient {
+    /// Publishes the messages of an NDJSON file, one per line, to `target`.
+    ///
+    /// Lines that cannot be read, decoded or validated, and messages no queue received, are reported
+    /// in [`ImportReport::errors`] and the import goes on. Messages whose id was already imported to
+    /// `target` (see [`ImportLedger`]) or appeared earlier in the file are skipped, unless
+    /// `options.force` is set; messages without an id are always published. The import stops on the
+    /// first failure of the broker, or when the file exceeds `options.max_bytes`.
+    ///
+    /// The ids of the published messages are recorded in `ledger` as they are published; the caller
+    /// then writes them to the ledger file with [`ImportLedger::flush`].
+    pub async fn import_messages<R>(
+        &self,
+        target: &ImportTarget,
+        mut reader: R,
+        options: &ImportOptions,
+        ledger: &ImportLedger,
+    ) -> ImportReport
+    where
+        R: AsyncBufRead + Unpin,
+    {
+        let mut report = ImportReport {
+            dry_run: options.dry_run,
+            ..Default::default()
+        };
+        let mut ticker = interval(Duration::from_secs(1) / options.rate.max(1));
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut seen = HashSet::new();
+        let mut read = 0u64;
+        let mut buffer = vec![];
+
+        for number in 1.. {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer).await {
+                Ok(0) => break,
+                Ok(n) => read += n as u64,
+                Err(e) => {
+                    report.error = Some(ServerError::InvalidRequest {
+                        reason: format!("cannot read line {}: {}", number, e),
+                    });
+                    break;
+                }
+            }
+            if read > options.max_bytes {
+                report.error = Some(ServerError::InvalidRequest {
+                    reason: format!(
+                        "the file is larger than {} bytes; it was imported up to line {}",
+                        options.max_bytes,
+                        number - 1
+                    ),
+                });
+                break;
+            }
+            let Ok(line) = std::str::from_utf8(&buffer) else {
+                report.lines += 1;
+                report.fail(number, None, "the line is not valid UTF-8".to_string());
+                continue;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            report.lines += 1;
+
+            let record = match serde_json::from_str::<ImportRecord>(line) {
+                Ok(record) => record,
+                Err(e) => {
+                    report.fail(number, None, e.to_string());
+                    continue;
+                }
+            };
+            let message_id = record.properties.message_id().map(str::to_string);
+            if let Some(id) = &message_id {
+                if !options.force && (seen.contains(id) || ledger.contains(target, id)) {
+                    report.skipped += 1;
+                    continue;
+                }
+            }
+            let (exchange, routing_key) = target.destination.route(&record);
+            let message = MessageToPublish {
+                routing_key: routing_key.clone(),
+                payload: record.payload,
+                payload_encoding: record.payload_encoding,
+                properties: record.properties,
+            };
+            if let Err(e) = message.validate() {
+                report.fail(number, message_id, e.to_string());
+                continue;
+            }
+
+            if options.dry_run {
+                report.published += 1;
+                if report.preview.len() < MAX_REPORTED_LINES {
+                    report.preview.push(ImportedLine {
+                        line: number,
+                        message_id: message_id.clone(),
+                        exchange,
+                        routing_key,
+                    });
+                }
+            } else {
+                ticker.tick().await;
+                match self.publish(&target.vhost, &exchange, &message).await {
+                    Ok(published) if published.routed => {
+                        report.published += 1;
+                        if let Some(id) = &message_id {
+                            ledger.record(target, id);
+                        }
+                    }
+                    Ok(_) => {
+                        report.fail(
+                            number,
+                            message_id,
+                            format!(
+                                "exchange {:?} routed the message with routing key {:?} to no queue",
+                                exchange, routing_key
+                            ),
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        report.error = Some(e);
+                        break;
+                    }
+                }
+            }
+            if let Some(id) = message_id {
+                seen.insert(id);
+            }
+        }
+        report
+    }
+}
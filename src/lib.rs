@@ -2,15 +2,27 @@ extern crate dotenv_codegen;
 
 use base64::prelude::*;
 
-use isahc::{prelude::*, AsyncBody, Request};
+use isahc::{http::Uri, prelude::*, AsyncBody, Request, Response};
 
 use serde::Deserialize;
 use std::collections::HashMap;
 
+pub mod audit;
+pub mod auth;
+pub mod client;
+pub mod clusters;
+pub mod config;
 pub mod constants;
 pub mod exceptions;
+pub mod export;
+pub mod import;
+pub mod logging;
+pub mod pattern;
+pub mod purge;
+pub mod rabbitmq;
 
-use constants::{RABBITMQ_MANAGEMENT_PASSWORD, RABBITMQ_MANAGEMENT_USERNAME};
+use exceptions::{snippet, ServerError};
+use logging::{next_request_id, UpstreamCall};
 
 /// Sends an HTTP GET request to the specified URI and deserializes the response body into the specified type.
 ///
@@ -25,15 +37,15 @@ use constants::{RABBITMQ_MANAGEMENT_PASSWORD, RABBITMQ_MANAGEMENT_USERNAME};
 ///
 /// # Returns
 ///
-/// - `Result<T, ()>`: On success, returns the deserialized response body of type `T`. On failure, returns an empty tuple `()`.
+/// - `Result<T, ServerError>`: On success, returns the deserialized response body of type `T`. On failure, returns a `ServerError`.
 ///
 /// # Errors
 ///
 /// This function will return an error if:
-/// - The URI cannot be parsed.
-/// - The TCP connection cannot be established.
-/// - The HTTP request fails.
-/// - The response body cannot be deserialized into the specified type.
+/// - The URI cannot be parsed (`ServerError::InvalidUri`).
+/// - The TCP connection cannot be established or the request times out (`ServerError::Transport`, `ServerError::Timeout`).
+/// - The server responds with a non-2xx status code (`ServerError::UpstreamStatus`).
+/// - The response body cannot be deserialized into the specified type (`ServerError::Deserialization`).
 ///
 /// # Example
 ///
@@ -62,7 +74,7 @@ use constants::{RABBITMQ_MANAGEMENT_PASSWORD, RABBITMQ_MANAGEMENT_USERNAME};
 pub async fn send_get<'a, T>(
     uri: &str,
     headers: Option<&'a HashMap<String, String>>,
-) -> Result<T, ()>
+) -> Result<T, ServerError>
 where
     T: for<'de> Deserialize<'de>,
 {
@@ -76,18 +88,18 @@ where
     }
 
     // Send the request and get the response to a string.
-    let request = request_builder.body(()).unwrap();
-    dbg!(&request);
+    let request = request_builder
+        .body(())
+        .map_err(|e| invalid_uri(uri, e.to_string()))?;
 
-    let mut response = isahc::send_async(request).await.unwrap();
-    let response_body = response.text().await.unwrap();
-
-    // TODO: Error handling
+    let call = UpstreamCall::start(&next_request_id(), &request, None);
+    let response = isahc::send_async(request)
+        .await
+        .map_err(|e| ServerError::from_transport(uri, e));
+    call.finish(&response);
 
     // Convert to a struct so that accessing the response is easier.
-    let response_data: T = serde_json::from_str(&response_body).unwrap();
-
-    Ok(response_data)
+    read_response(uri, response?).await
 }
 
 /// Sends an asynchronous HTTP POST request.
@@ -103,7 +115,8 @@ where
 ///
 /// # Returns
 ///
-/// * `Result<T, ()>` - On success, returns the deserialized response body of type `T`. On failure, returns an empty tuple `()`.
+/// * `Result<T, ServerError>` - On success, returns the deserialized response body of type `T`. On failure, returns a `ServerError`
+///   describing whether the URI, the transport, the HTTP status or the response body was at fault.
 ///
 /// # Type Parameters
 ///
@@ -143,9 +156,10 @@ pub async fn send_post<'a, T, B>(
     uri: &str,
     headers: Option<&'a HashMap<String, String>>,
     body: B,
-) -> Result<T, ()>
+) -> Result<T, ServerError>
 where
     AsyncBody: From<B>,
+    B: AsRef<[u8]>,
     T: for<'de> Deserialize<'de>,
 {
     let mut request_builder = Request::post(uri);
@@ -157,57 +171,83 @@ where
         }
     }
 
-    let request = request_builder.body(body).unwrap();
-    let mut response = isahc::send_async(request).await.unwrap();
-    let response_body = response.text().await.unwrap();
+    let request = request_builder
+        .body(body)
+        .map_err(|e| invalid_uri(uri, e.to_string()))?;
 
-    let response_data: T = serde_json::from_str(&response_body).unwrap();
+    let call = UpstreamCall::start(&next_request_id(), &request, Some(request.body().as_ref()));
+    let response = isahc::send_async(request.map(AsyncBody::from))
+        .await
+        .map_err(|e| ServerError::from_transport(uri, e));
+    call.finish(&response);
 
-    Ok(response_data)
+    read_response(uri, response?).await
 }
 
-/// Prepares the authorization headers for RabbitMQ management API requests.
-///
-/// This function reads the RabbitMQ management username and password from environment variables,
-/// encodes them in Base64, and constructs the `Authorization` header required for making API requests.
-///
-/// # Returns
-///
-/// A `HashMap` containing the `Authorization` header with the encoded credentials.
-///
-/// # Panics
+/// Reads the body of a management API response and deserializes it into `T`.
 ///
-/// This function will panic if the environment variables `RABBITMQ_MANAGEMENT_USERNAME` or
-/// `RABBITMQ_MANAGEMENT_PASSWORD` are not set.
+/// Non-2xx responses are turned into `ServerError::UpstreamStatus` carrying the body the management
+/// API sent back, so that callers can tell e.g. a 401 from a 404.
+pub(crate) async fn read_response<T>(
+    uri: &str,
+    mut response: Response<AsyncBody>,
+) -> Result<T, ServerError>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    let status = response.status();
+    let response_body = response.text().await.map_err(|e| ServerError::Transport {
+        uri: uri.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    if !status.is_success() {
+        return Err(ServerError::UpstreamStatus {
+            uri: uri.to_string(),
+            status: status.as_u16(),
+            body: snippet(&response_body),
+        });
+    }
+
+    // An empty body (e.g. `204 No Content` after a DELETE) reads as `null`, which deserializes into `()`.
+    let json = if response_body.is_empty() {
+        "null"
+    } else {
+        &response_body
+    };
+    serde_json::from_str(json).map_err(|e| ServerError::Deserialization {
+        uri: uri.to_string(),
+        reason: e.to_string(),
+        body: snippet(&response_body),
+    })
+}
+
+pub(crate) fn invalid_uri(uri: &str, reason: String) -> ServerError {
+    ServerError::InvalidUri {
+        uri: uri.to_string(),
+        reason,
+    }
+}
+
+/// Builds the value of a Basic `Authorization` header for the given credentials.
 ///
 /// # Example
 ///
+/// ```rust
+/// use rabbitmq_messages_management::basic_authorization;
+/// assert_eq!(basic_authorization("guest", "guest"), "Basic Z3Vlc3Q6Z3Vlc3Q=");
 /// ```
-/// # #[cfg(doctest)] {
-/// use std::collections::HashMap;
-/// use rabbitmq_messages_management::prepare_authorization_headers;
-/// let headers = prepare_authorization_headers();
-/// assert!(headers.contains_key("Authorization"));
-/// # }
-/// ```
-pub fn prepare_authorization_headers() -> HashMap<String, String> {
-    // TODO: Error handling
-    HashMap::from([(
-        "Authorization".to_string(),
-        format!(
-            "Basic {}",
-            BASE64_STANDARD.encode(format!(
-                "{}:{}",
-                dotenv::var(RABBITMQ_MANAGEMENT_USERNAME)
-                    .expect("RABBITMQ_MANAGEMENT_USERNAME not set"),
-                dotenv::var(RABBITMQ_MANAGEMENT_PASSWORD)
-                    .expect("RABBITMQ_MANAGEMENT_PASSWORD not set")
-            ))
-        ),
-    )])
+pub fn basic_authorization(username: &str, password: &str) -> String {
+    format!(
+        "Basic {}",
+        BASE64_STANDARD.encode(format!("{}:{}", username, password))
+    )
 }
 
-// Constructs a full URL by concatenating the root URI and the path.
+/// Constructs a full URL by concatenating the root URI and the path.
+///
+/// Trailing slashes on the root and leading slashes on the path are normalised, so that exactly one
+/// `/` separates them. The path is expected to be encoded already (see `rabbitmq::path::ApiPath`).
 ///
 /// # Arguments
 ///
@@ -216,7 +256,8 @@ pub fn prepare_authorization_headers() -> HashMap<String, String> {
 ///
 /// # Returns
 ///
-/// - `Result<String, ()>`: On success, returns the full URL as a `String`. On failure, returns an empty tuple `()`.
+/// - `Result<String, ServerError>`: On success, returns the full URL as a `String`. On failure, returns
+///   `ServerError::InvalidUri` if the result is not a valid URI.
 ///
 /// # Example
 ///
@@ -226,7 +267,16 @@ pub fn prepare_authorization_headers() -> HashMap<String, String> {
 /// let path = "api/v1/resource";
 /// let full_url = prepare_url(root_uri, path).unwrap();
 /// assert_eq!(full_url, "http://example.com/api/v1/resource");
+/// assert_eq!(prepare_url("http://example.com//", "/api").unwrap(), "http://example.com/api");
 /// ```
-pub fn prepare_url(root_uri: &str, path: &str) -> Result<String, ()> {
-    Ok(format!("{}/{}", root_uri, path))
+pub fn prepare_url(root_uri: &str, path: &str) -> Result<String, ServerError> {
+    let url = format!(
+        "{}/{}",
+        root_uri.trim_end_matches('/'),
+        path.trim_start_matches('/')
+    );
+    match url.parse::<Uri>() {
+        Ok(_) => Ok(url),
+        Err(e) => Err(invalid_uri(&url, e.to_string())),
+    }
 }
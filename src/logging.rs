@@ -0,0 +1,203 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        RwLock,
+    },
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use isahc::{
+    http::{HeaderMap, Uri},
+    Request, Response,
+};
+use log::Level;
+
+use crate::{config::LoggingConfig, exceptions::ServerError};
+
+/// Replacement for redacted header values and payload fields.
+pub const REDACTED: &str = "<redacted>";
+
+/// Headers whose values are never logged.
+const SENSITIVE_HEADERS: [&str; 3] = ["authorization", "proxy-authorization", "cookie"];
+
+/// Payload fields redacted from logged request bodies, set by [`configure`].
+static REDACTED_FIELDS: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Number of request ids generated so far.
+static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Applies the logging settings.
+///
+/// The level, if set, replaces the maximum level of the logger (Rocket's `log_level`).
+pub fn configure(config: &LoggingConfig) {
+    if let Some(level) = config.level {
+        log::set_max_level(level);
+    }
+    *REDACTED_FIELDS.write().unwrap_or_else(|e| e.into_inner()) = config.redact_fields.clone();
+}
+
+/// Id of the API request being served, cached in the request so that its errors and management API
+/// calls are logged with it.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Generates a request id, unique within this process and unlikely to repeat across restarts.
+pub fn next_request_id() -> String {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let count = REQUEST_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{:x}-{:x}", started & 0xff_ffff, count)
+}
+
+/// Formats headers as `name: value` pairs, redacting credentials.
+pub fn redact_headers(headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                REDACTED
+            } else {
+                value.to_str().unwrap_or("<binary>")
+            };
+            format!("{}: {}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Replaces the values of the configured fields, at any depth, of a JSON body.
+///
+/// Bodies that are not JSON are summarized by their size, since they cannot be redacted.
+///
+/// # Example
+///
+/// ```rust
+/// use rabbitmq_messages_management::logging::redact_body;
+/// let fields = vec!["payload".to_string()];
+/// assert_eq!(
+///     redact_body(r#"{"routing_key":"orders","payload":"secret"}"#, &fields),
+///     r#"{"payload":"<redacted>","routing_key":"orders"}"#
+/// );
+/// assert_eq!(redact_body("not json", &fields), "<8 bytes>");
+/// ```
+pub fn redact_body(body: &str, fields: &[String]) -> String {
+    fn redact(value: &mut serde_json::Value, fields: &[String]) {
+        match value {
+            serde_json::Value::Object(object) => {
+                for (key, value) in object.iter_mut() {
+                    if fields.contains(key) {
+                        *value = serde_json::Value::String(REDACTED.to_string());
+                    } else {
+                        redact(value, fields);
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|value| redact(value, fields))
+            }
+            _ => {}
+        }
+    }
+
+    match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(mut value) => {
+            redact(&mut value, fields);
+            value.to_string()
+        }
+        Err(_) => format!("<{} bytes>", body.len()),
+    }
+}
+
+/// Removes the user information (`user:password@`) from a URL.
+fn redact_url(uri: &Uri) -> String {
+    match uri.authority() {
+        Some(authority) if authority.as_str().contains('@') => {
+            let host = authority.as_str().rsplit('@').next().unwrap_or_default();
+            format!(
+                "{}://{}@{}{}",
+                uri.scheme_str().unwrap_or("http"),
+                REDACTED,
+                host,
+                uri.path_and_query()
+                    .map(|path| path.as_str())
+                    .unwrap_or("/")
+            )
+        }
+        _ => uri.to_string(),
+    }
+}
+
+/// A management API call being made, logged when it starts (at debug level) and when it completes.
+pub(crate) struct UpstreamCall {
+    /// Id correlating the call with the request that caused it.
+    request_id: String,
+    /// HTTP method of the call.
+    method: String,
+    /// Redacted URL of the call.
+    url: String,
+    /// When the call started.
+    started: Instant,
+}
+
+impl UpstreamCall {
+    /// Logs the request, with its headers and body redacted.
+    pub(crate) fn start<B>(request_id: &str, request: &Request<B>, body: Option<&[u8]>) -> Self {
+        let call = UpstreamCall {
+            request_id: request_id.to_string(),
+            method: request.method().to_string(),
+            url: redact_url(request.uri()),
+            started: Instant::now(),
+        };
+        if log::log_enabled!(Level::Debug) {
+            let body = body
+                .map(|body| {
+                    let fields = REDACTED_FIELDS.read().unwrap_or_else(|e| e.into_inner());
+                    redact_body(&String::from_utf8_lossy(body), &fields)
+                })
+                .unwrap_or_default();
+            log::debug!(
+                "upstream request request_id={} method={} url={} headers=[{}] body={}",
+                call.request_id,
+                call.method,
+                call.url,
+                redact_headers(request.headers()),
+                body
+            );
+        }
+        call
+    }
+
+    /// Logs the status and latency of the call, or the error it failed with.
+    pub(crate) fn finish<B>(self, result: &Result<Response<B>, ServerError>) {
+        let latency_ms = self.started.elapsed().as_millis();
+        match result {
+            Ok(response) => {
+                let status = response.status();
+                let level = if status.is_success() {
+                    Level::Info
+                } else {
+                    Level::Warn
+                };
+                log::log!(
+                    level,
+                    "upstream response request_id={} method={} url={} status={} latency_ms={}",
+                    self.request_id,
+                    self.method,
+                    self.url,
+                    status.as_u16(),
+                    latency_ms
+                );
+            }
+            Err(error) => log::warn!(
+                "upstream failure request_id={} method={} url={} error={:?} latency_ms={}",
+                self.request_id,
+                self.method,
+                self.url,
+                error.code(),
+                latency_ms
+            ),
+        }
+    }
+}
@@ -1,48 +1,194 @@
-use std::collections::HashMap;
-
-use rocket::{fs::FileServer, serde::json::Json, Request};
+use rabbitmq_messages_management::{
+    audit::AuditLog,
+    auth::{Authenticator, Authorizer},
+    clusters::Clusters,
+    config::AppConfig,
+    exceptions::ErrorBody,
+    import::ImportLedger,
+    logging,
+    purge::PurgeConfirmations,
+};
+use rocket::{fairing::AdHoc, fs::FileServer, serde::json::Json, Build, Request, Rocket};
 use routes::{
+    audit::{audit, Auditor},
+    auth,
+    clusters::clusters,
+    exchanges::publish,
+    export::export,
+    guard_error,
+    import::import,
+    moves::move_messages,
+    purge::{purge, purge_confirmation},
     queues::{messages, queues},
+    request_id::RequestIds,
+    scan::scan,
+    session::{login, logout, session},
     vhosts::vhosts,
 };
 
 #[macro_use]
 extern crate rocket;
 
-mod constants;
-mod rabbitmq;
 mod routes;
 
 #[catch(500)]
-fn internal_error() -> Json<HashMap<String, String>> {
-    Json(HashMap::from([(
-        "reason".to_string(),
+fn internal_error() -> Json<ErrorBody> {
+    Json(ErrorBody::new(
+        "internal_error",
         "Server error. Please try again later".to_string(),
-    )]))
+    ))
 }
 
 #[catch(400)]
-fn bad_request(req: &Request) -> Json<HashMap<String, String>> {
-    Json(HashMap::from([(
-        "reason".to_string(),
-        "Bad request".to_string(),
-    )]))
+fn bad_request(_req: &Request) -> Json<ErrorBody> {
+    Json(ErrorBody::new("bad_request", "Bad request".to_string()))
+}
+
+#[catch(401)]
+fn unauthorized(req: &Request) -> Json<ErrorBody> {
+    match guard_error(req) {
+        Some(error) => Json(ErrorBody::from(error)),
+        None => Json(ErrorBody::new("unauthorized", "Unauthorized".to_string())),
+    }
+}
+
+#[catch(403)]
+fn forbidden(req: &Request) -> Json<ErrorBody> {
+    match guard_error(req) {
+        Some(error) => Json(ErrorBody::from(error)),
+        None => Json(ErrorBody::new("forbidden", "Forbidden".to_string())),
+    }
+}
+
+#[catch(422)]
+fn unprocessable_entity(req: &Request) -> Json<ErrorBody> {
+    match guard_error(req) {
+        Some(error) => Json(ErrorBody::from(error)),
+        None => Json(ErrorBody::new(
+            "invalid_parameters",
+            "Invalid parameters".to_string(),
+        )),
+    }
 }
 
 #[catch(404)]
-fn not_found(req: &Request) -> Json<HashMap<String, String>> {
-    Json(HashMap::from([(
-        "reason".to_string(),
-        format!("{:?} not found.", req.uri().path()),
-    )]))
+fn not_found(req: &Request) -> Json<ErrorBody> {
+    match guard_error(req) {
+        Some(error) => Json(ErrorBody::from(error)),
+        None => Json(ErrorBody::new(
+            "not_found",
+            format!("{:?} not found.", req.uri().path()),
+        )),
+    }
+}
+
+/// Loads and validates the application configuration, then manages it, the management API clients
+/// of all clusters, the authenticator, the authorizer, the audit log and the purge confirmations as
+/// state. Launch is aborted with a clear message if any of them fails.
+async fn configure(rocket: Rocket<Build>) -> Result<Rocket<Build>, Rocket<Build>> {
+    let config = match AppConfig::from_figment(rocket.figment()) {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("{}", e);
+            return Err(rocket);
+        }
+    };
+    let clusters = match Clusters::from_config(&config) {
+        Ok(clusters) => clusters,
+        Err(e) => {
+            log::error!("Failed to create management API clients: {}", e);
+            return Err(rocket);
+        }
+    };
+    let authenticator = match Authenticator::from_config(&config.auth) {
+        Ok(authenticator) => authenticator,
+        Err(e) => {
+            log::error!("{}", e);
+            return Err(rocket);
+        }
+    };
+
+    let authorizer = Authorizer::from_config(&config.auth);
+    let confirmations = PurgeConfirmations::new(config.purge.token_ttl());
+    let ledger = match ImportLedger::open(&config.import) {
+        Ok(ledger) => ledger,
+        Err(e) => {
+            log::error!("{}", e);
+            return Err(rocket);
+        }
+    };
+    let audit_log = match AuditLog::open(&config.audit) {
+        Ok(audit_log) => audit_log,
+        Err(e) => {
+            log::error!("{}", e);
+            return Err(rocket);
+        }
+    };
+
+    let rocket = if config.static_dir.is_dir() {
+        rocket.mount("/", FileServer::from(&config.static_dir))
+    } else {
+        rocket
+    };
+    Ok(rocket
+        .manage(clusters)
+        .manage(authenticator)
+        .manage(authorizer)
+        .manage(audit_log)
+        .manage(confirmations)
+        .manage(ledger)
+        .manage(config))
 }
 
+/// Mount points of the API, under which errors are rendered as JSON [`ErrorBody`]s.
+const API_BASES: [&str; 3] = ["/audit", "/auth", "/clusters"];
+
 #[launch]
 fn rocket() -> _ {
-    rocket::build()
-        .mount("/", FileServer::from("./static"))
-        .mount("/queues", routes![queues, messages])
-        .mount("/vhosts", routes![vhosts])
-        .register("/queues", catchers![internal_error, not_found, bad_request])
-        .register("/vhosts", catchers![internal_error, not_found, bad_request])
+    let rocket = rocket::custom(AppConfig::figment())
+        .attach(AdHoc::try_on_ignite("Application configuration", configure))
+        .attach(AdHoc::on_liftoff("Logging", |rocket| {
+            Box::pin(async move {
+                // Rocket sets the log level when it ignites, so the configured one is applied afterwards.
+                if let Some(config) = rocket.state::<AppConfig>() {
+                    logging::configure(&config.logging);
+                }
+            })
+        }))
+        .attach(RequestIds)
+        .attach(Auditor)
+        .mount("/audit", routes![audit])
+        .mount("/auth", routes![auth::login, auth::me, auth::logout])
+        .mount(
+            "/clusters",
+            routes![
+                clusters,
+                vhosts,
+                queues,
+                messages,
+                scan,
+                export,
+                move_messages,
+                purge_confirmation,
+                purge,
+                publish,
+                import,
+                login,
+                session,
+                logout
+            ],
+        );
+    API_BASES.into_iter().fold(rocket, |rocket, base| {
+        rocket.register(
+            base,
+            catchers![
+                internal_error,
+                not_found,
+                bad_request,
+                unprocessable_entity,
+                unauthorized,
+                forbidden
+            ],
+        )
+    })
 }
@@ -0,0 +1,41 @@
+/// Matches `text` against a glob `pattern`, where `*` matches any sequence of characters (including
+/// none and `/`) and `?` matches exactly one character. Every other character matches itself.
+///
+/// # Example
+///
+/// ```rust
+/// use rabbitmq_messages_management::pattern::glob_matches;
+/// assert!(glob_matches("orders-*", "orders-eu"));
+/// assert!(glob_matches("*", "/"));
+/// assert!(glob_matches("q?", "q1"));
+/// assert!(!glob_matches("orders-*", "payments"));
+/// ```
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen, and of the text character it is currently matched up to.
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
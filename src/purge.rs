@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    fs::OpenOptions,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use base64::prelude::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    client::ManagementClient,
+    exceptions::ServerError,
+    rabbitmq::{
+        filters::MessageFilter,
+        queues::{PayloadEncoding, ResponseForQueryingMessages},
+        scan::{ScanOptions, ScanStop},
+    },
+};
+
+/// Number of new messages fetched at each step of a snapshot.
+pub const SNAPSHOT_BATCH_SIZE: u64 = 500;
+
+/// A token confirming that a queue may be purged, issued with the number of messages it holds.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct PurgeConfirmation {
+    /// Token to pass to the purge request.
+    pub token: String,
+    /// Number of messages in the queue when the token was issued.
+    pub messages: u64,
+    /// Number of those messages a purge removes (the others are held by consumers).
+    pub messages_ready: u64,
+    /// When the token expires.
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+/// The purge a token was issued for.
+#[derive(Debug, PartialEq, Eq)]
+struct PendingPurge {
+    /// Name of the principal the token was issued to.
+    principal: String,
+    /// Cluster of the queue.
+    cluster: String,
+    /// Vhost of the queue.
+    vhost: String,
+    /// Name of the queue.
+    queue: String,
+}
+
+/// Purge confirmation tokens that have been issued and not used yet.
+///
+/// A token is bound to the principal it was issued to and to one queue, expires after a while and can
+/// only be used once.
+#[derive(Debug)]
+pub struct PurgeConfirmations {
+    /// How long a token stays valid.
+    ttl: Duration,
+    /// Pending purges, by token, with their expiry.
+    pending: Mutex<HashMap<String, (PendingPurge, Instant)>>,
+}
+
+impl PurgeConfirmations {
+    /// Creates an empty set of tokens valid for `ttl`.
+    pub fn new(ttl: Duration) -> PurgeConfirmations {
+        PurgeConfirmations {
+            ttl,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Issues a token allowing `principal` to purge a queue.
+    pub fn issue(
+        &self,
+        principal: &str,
+        cluster: &str,
+        vhost: &str,
+        queue: &str,
+        messages: u64,
+        messages_ready: u64,
+    ) -> PurgeConfirmation {
+        let mut bytes = [0u8; 24];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = BASE64_URL_SAFE_NO_PAD.encode(bytes);
+        let purge = PendingPurge::new(principal, cluster, vhost, queue);
+
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.retain(|_, (_, expires)| *expires > now);
+        pending.insert(token.clone(), (purge, now + self.ttl));
+
+        PurgeConfirmation {
+            token,
+            messages,
+            messages_ready,
+            expires_at: OffsetDateTime::now_utc() + self.ttl,
+        }
+    }
+
+    /// Checks that a token was issued to `principal` for this queue and has not expired, without
+    /// using it up.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Conflict` if the token is unknown, expired, already used, or was issued
+    /// for another principal or queue.
+    pub fn check(
+        &self,
+        token: &str,
+        principal: &str,
+        cluster: &str,
+        vhost: &str,
+        queue: &str,
+    ) -> Result<(), ServerError> {
+        let expected = PendingPurge::new(principal, cluster, vhost, queue);
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        match pending.get(token) {
+            Some((purge, expires)) if *purge == expected && *expires > Instant::now() => Ok(()),
+            _ => Err(invalid_token()),
+        }
+    }
+
+    /// Uses up a token, checking it like [`PurgeConfirmations::check`]. The token can be given back
+    /// with [`PurgeConfirmations::reinstate`] if the purge then fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Conflict` if the token is unknown, expired, already used, or was issued
+    /// for another principal or queue.
+    pub fn redeem(
+        &self,
+        token: &str,
+        principal: &str,
+        cluster: &str,
+        vhost: &str,
+        queue: &str,
+    ) -> Result<RedeemedToken, ServerError> {
+        let expected = PendingPurge::new(principal, cluster, vhost, queue);
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        match pending.remove(token) {
+            Some((purge, expires)) if purge == expected && expires > Instant::now() => {
+                Ok(RedeemedToken {
+                    token: token.to_string(),
+                    purge,
+                    expires,
+                })
+            }
+            _ => Err(invalid_token()),
+        }
+    }
+
+    /// Makes a redeemed token usable again, until it was due to expire, because the purge it was
+    /// redeemed for did not happen.
+    pub fn reinstate(&self, redeemed: RedeemedToken) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.insert(redeemed.token, (redeemed.purge, redeemed.expires));
+    }
+}
+
+/// A token used up by [`PurgeConfirmations::redeem`].
+#[derive(Debug)]
+pub struct RedeemedToken {
+    /// The token.
+    token: String,
+    /// The purge it was issued for.
+    purge: PendingPurge,
+    /// When it expires.
+    expires: Instant,
+}
+
+impl PendingPurge {
+    /// The purge of `queue` by `principal`.
+    fn new(principal: &str, cluster: &str, vhost: &str, queue: &str) -> PendingPurge {
+        PendingPurge {
+            principal: principal.to_string(),
+            cluster: cluster.to_string(),
+            vhost: vhost.to_string(),
+            queue: queue.to_string(),
+        }
+    }
+}
+
+/// Error for a token that cannot be used for the requested purge.
+fn invalid_token() -> ServerError {
+    ServerError::Conflict {
+        reason: "the confirmation token is unknown, expired, already used or was issued for \
+                 another queue; request a new one"
+            .to_string(),
+    }
+}
+
+impl ManagementClient {
+    /// Fetches every ready message of a queue, with base64 payloads, to save them before a purge.
+    ///
+    /// The messages are fetched in batches and requeued, like a scan (see
+    /// [`ManagementClient::scan_queue`]), so the queue is left as it was, but for the redelivered
+    /// flags.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Conflict` if the queue holds more than `max_messages` messages, or if not
+    /// all of its messages could be fetched: the scan did not reach the end of the queue, or fetched
+    /// fewer messages than the queue held according to its statistics or to the broker's count of
+    /// the messages remaining behind the first one. Identical messages, which the scan examines once,
+    /// make a snapshot incomplete too.
+    pub async fn snapshot_queue(
+        &self,
+        vhost: &str,
+        queue_name: &str,
+        messages_ready: u64,
+        max_messages: u64,
+    ) -> Result<Vec<ResponseForQueryingMessages>, ServerError> {
+        let too_many = |messages: u64| ServerError::Conflict {
+            reason: format!(
+                "the queue holds {} messages, more than the {} a snapshot may hold",
+                messages, max_messages
+            ),
+        };
+        if messages_ready > max_messages {
+            return Err(too_many(messages_ready));
+        }
+        let options = ScanOptions {
+            batch_size: SNAPSHOT_BATCH_SIZE,
+            // One message more than allowed is fetched, to tell a full queue from a larger one.
+            limit: max_messages + 1,
+            max_matches: max_messages + 1,
+            encoding: PayloadEncoding::Base64,
+            truncate: None,
+            decode: false,
+        };
+        let mut scan = self
+            .scan_queue(
+                vhost,
+                queue_name,
+                MessageFilter::default().compile()?,
+                options,
+            )
+            .await?;
+        let mut messages: Vec<ResponseForQueryingMessages> = vec![];
+        // Every message matches and none is decompressed, so batches are cheap to examine.
+        while let Some(fetched) = scan.next_batch().await? {
+            messages.extend(scan.absorb(fetched));
+        }
+
+        // The first message fetched tells how many messages were behind it, exactly, unlike the
+        // sampled statistics of the queue.
+        let expected = messages
+            .first()
+            .map_or(0, |first| first.message_count() + 1)
+            .max(messages_ready);
+        let saved = messages.len() as u64;
+        if saved > max_messages || expected > max_messages {
+            return Err(too_many(saved.max(expected)));
+        }
+        let stopped = scan.progress().stopped;
+        if saved < expected || !matches!(stopped, Some(ScanStop::Completed | ScanStop::QueueEmpty))
+        {
+            let cause = match stopped {
+                Some(ScanStop::HeadRequeued) => {
+                    "the queue puts fetched messages back at its head, hiding the others"
+                }
+                _ => "messages are identical, or the queue changed during the snapshot",
+            };
+            return Err(ServerError::Conflict {
+                reason: format!(
+                    "only {} of the {} messages of the queue could be saved ({}), so it was not \
+                     purged; the same token can purge it without a snapshot, or try again",
+                    saved, expected, cause
+                ),
+            });
+        }
+        Ok(messages)
+    }
+}
+
+/// Writes the messages of a queue to a new JSON-lines file in `dir`, one message per line, and returns
+/// its path.
+///
+/// Each line has the shape of a message returned by the API, which is also accepted by the publish
+/// endpoint, so a purged queue can be restored by republishing the lines.
+///
+/// # Errors
+///
+/// Returns `ServerError::Configuration` if the file cannot be written.
+pub fn write_snapshot(
+    dir: &Path,
+    cluster: &str,
+    vhost: &str,
+    queue: &str,
+    messages: &[ResponseForQueryingMessages],
+) -> Result<PathBuf, ServerError> {
+    let now = OffsetDateTime::now_utc();
+    let name = format!(
+        "{}-{}-{}-{}.ndjson",
+        file_name_part(cluster),
+        file_name_part(vhost),
+        file_name_part(queue),
+        now.unix_timestamp_nanos() / 1_000_000
+    );
+    let path = dir.join(name);
+    let failed = |e: std::io::Error| ServerError::Configuration {
+        reason: format!("cannot write snapshot {}: {}", path.display(), e),
+    };
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map_err(failed)?;
+    let mut writer = BufWriter::new(file);
+    for message in messages {
+        let line = serde_json::to_string(message).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+        writeln!(writer, "{}", line).map_err(failed)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| failed(e.into_error()))?
+        .sync_all()
+        .map_err(failed)?;
+    Ok(path)
+}
+
+/// Replaces the characters of a name that are unsafe in a file name.
+pub(crate) fn file_name_part(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,159 @@
+use std::io::{self, Read};
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+/// Maximum number of bytes a payload is decompressed to, so that a small compressed payload cannot
+/// exhaust the memory of the server.
+pub const MAX_DECODED_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Maximum number of bytes the payloads fetched by one request are decompressed to, in total, so that
+/// many small compressed payloads cannot exhaust the memory of the server either.
+pub const MAX_DECODED_BYTES_PER_REQUEST: u64 = 64 * 1024 * 1024;
+
+/// Number of bytes left to decompress payloads to, shared by the messages of one request.
+///
+/// Payloads are decompressed up to what is left, and at most [`MAX_DECODED_BYTES`] each; once the
+/// budget is spent, compressed payloads are left as they are.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeBudget {
+    /// Bytes left.
+    remaining: u64,
+}
+
+impl DecodeBudget {
+    /// A budget of `bytes` decompressed bytes.
+    pub fn new(bytes: u64) -> DecodeBudget {
+        DecodeBudget { remaining: bytes }
+    }
+
+    /// Number of bytes left.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Maximum number of bytes the next payload may be decompressed to.
+    pub fn limit(&self) -> u64 {
+        self.remaining.min(MAX_DECODED_BYTES)
+    }
+
+    /// Accounts for a payload decompressed to `bytes` bytes.
+    pub fn spend(&mut self, bytes: u64) {
+        self.remaining = self.remaining.saturating_sub(bytes);
+    }
+}
+
+impl Default for DecodeBudget {
+    fn default() -> Self {
+        DecodeBudget::new(MAX_DECODED_BYTES_PER_REQUEST)
+    }
+}
+
+/// Compression applied to a payload, as told by its `content_encoding` property.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// `gzip` (or `x-gzip`).
+    Gzip,
+    /// `deflate`: zlib-wrapped deflate, as in HTTP, or raw deflate, which some producers send.
+    Deflate,
+    /// `br`: Brotli.
+    Brotli,
+    /// `zstd`: Zstandard.
+    Zstd,
+}
+
+/// A decompressed payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decompressed {
+    /// The decompressed bytes.
+    pub bytes: Vec<u8>,
+    /// Indicates if `bytes` are the whole payload. They are only its beginning when the compressed
+    /// payload was truncated, or decompressed to more than the limit.
+    pub complete: bool,
+}
+
+impl ContentEncoding {
+    /// The compression named by a `content_encoding` property, ignoring case, or `None` if it is not
+    /// a supported compression (e.g. `identity`, or a character encoding such as `utf-8`).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::rabbitmq::content::ContentEncoding;
+    /// assert_eq!(ContentEncoding::parse(" GZIP"), Some(ContentEncoding::Gzip));
+    /// assert_eq!(ContentEncoding::parse("utf-8"), None);
+    /// ```
+    pub fn parse(content_encoding: &str) -> Option<ContentEncoding> {
+        match content_encoding.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentEncoding::Gzip),
+            "deflate" => Some(ContentEncoding::Deflate),
+            "br" => Some(ContentEncoding::Brotli),
+            "zstd" => Some(ContentEncoding::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Decompresses a payload, up to `limit` bytes.
+    ///
+    /// `truncated` tells that `encoded` is only the beginning of the payload: whatever could be
+    /// decompressed before the data ran out is then returned, as an incomplete payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the decompressor if `encoded` is not valid for this compression, or if
+    /// nothing could be decompressed from a truncated payload.
+    pub fn decompress(
+        &self,
+        encoded: &[u8],
+        truncated: bool,
+        limit: u64,
+    ) -> io::Result<Decompressed> {
+        match self {
+            ContentEncoding::Gzip => read_to_limit(GzDecoder::new(encoded), truncated, limit),
+            ContentEncoding::Deflate if is_zlib(encoded) => {
+                read_to_limit(ZlibDecoder::new(encoded), truncated, limit)
+            }
+            ContentEncoding::Deflate => {
+                read_to_limit(DeflateDecoder::new(encoded), truncated, limit)
+            }
+            ContentEncoding::Brotli => {
+                read_to_limit(brotli::Decompressor::new(encoded, 4096), truncated, limit)
+            }
+            ContentEncoding::Zstd => read_to_limit(zstd::Decoder::new(encoded)?, truncated, limit),
+        }
+    }
+}
+
+/// Indicates if deflate data starts with a zlib header (RFC 1950): deflate compression method and a
+/// valid header checksum.
+fn is_zlib(encoded: &[u8]) -> bool {
+    match encoded {
+        [cmf, flg, ..] => cmf & 0x0f == 8 && (u16::from(*cmf) << 8 | u16::from(*flg)) % 31 == 0,
+        _ => false,
+    }
+}
+
+/// Reads a decompressor until its end or `limit` bytes.
+fn read_to_limit<R: Read>(decoder: R, truncated: bool, limit: u64) -> io::Result<Decompressed> {
+    let mut bytes = vec![];
+    // One byte more than the limit is read, to tell a payload of exactly `limit` bytes from a
+    // larger one.
+    let mut decoder = decoder.take(limit.saturating_add(1));
+    match decoder.read_to_end(&mut bytes) {
+        Ok(_) if bytes.len() as u64 > limit => {
+            bytes.truncate(limit as usize);
+            Ok(Decompressed {
+                bytes,
+                complete: false,
+            })
+        }
+        Ok(_) => Ok(Decompressed {
+            bytes,
+            complete: !truncated,
+        }),
+        Err(_) if truncated && !bytes.is_empty() => Ok(Decompressed {
+            bytes,
+            complete: false,
+        }),
+        Err(e) => Err(e),
+    }
+}
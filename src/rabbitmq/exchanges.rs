@@ -0,0 +1,164 @@
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::ManagementClient,
+    exceptions::{FieldError, ServerError},
+};
+
+use super::{
+    path::ApiPath,
+    queues::{Properties, ResponseForQueryingMessages},
+};
+
+/// Name the management API gives the default exchange, which routes messages to the queue named by
+/// their routing key.
+pub const DEFAULT_EXCHANGE: &str = "amq.default";
+
+/// How the payload of a message to publish is encoded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum PublishEncoding {
+    /// The payload is published as is.
+    #[default]
+    String,
+    /// The payload is base64 encoded, e.g. because it is binary; the decoded bytes are published.
+    Base64,
+}
+
+/// A message to publish to an exchange.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct MessageToPublish {
+    /// Routing key the message is published with.
+    #[serde(default)]
+    pub routing_key: String,
+    /// Payload of the message.
+    pub payload: String,
+    /// Encoding of the payload.
+    #[serde(default)]
+    pub payload_encoding: PublishEncoding,
+    /// Properties of the message, including its headers.
+    #[serde(default)]
+    pub properties: Properties,
+}
+
+impl MessageToPublish {
+    /// A copy of a fetched message, to be published with `routing_key`.
+    pub fn from_message(message: &ResponseForQueryingMessages, routing_key: &str) -> Self {
+        MessageToPublish {
+            routing_key: routing_key.to_string(),
+            payload: message.payload().to_string(),
+            payload_encoding: match message.payload_encoding() {
+                "base64" => PublishEncoding::Base64,
+                _ => PublishEncoding::String,
+            },
+            properties: message.properties().clone(),
+        }
+    }
+
+    /// Checks the message before it is sent to the broker.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidParameters` if the payload is declared as base64 but is not.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::rabbitmq::exchanges::{MessageToPublish, PublishEncoding};
+    /// let message = MessageToPublish {
+    ///     payload: "not base64!".to_string(),
+    ///     payload_encoding: PublishEncoding::Base64,
+    ///     ..Default::default()
+    /// };
+    /// assert!(message.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), ServerError> {
+        if self.payload_encoding == PublishEncoding::Base64
+            && BASE64_STANDARD.decode(&self.payload).is_err()
+        {
+            return Err(ServerError::InvalidParameters {
+                errors: vec![FieldError {
+                    field: "payload".to_string(),
+                    message: "expected base64 since payload_encoding is base64".to_string(),
+                }],
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Body of a publish request to the management API.
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+struct PublishRequest<'a> {
+    /// Properties of the message, without the unset ones.
+    properties: serde_json::Map<String, serde_json::Value>,
+    /// Routing key the message is published with.
+    routing_key: &'a str,
+    /// Payload of the message.
+    payload: &'a str,
+    /// Encoding of the payload.
+    payload_encoding: PublishEncoding,
+}
+
+/// Outcome of publishing a message.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct PublishResponse {
+    /// Indicates if the message was routed to at least one queue. Unroutable messages are dropped
+    /// (or sent to the exchange's alternate exchange).
+    pub routed: bool,
+}
+
+impl ManagementClient {
+    /// Publishes a message to an exchange of a virtual host.
+    ///
+    /// The default exchange is named [`DEFAULT_EXCHANGE`] by the management API.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidParameters` if the message is invalid (see
+    /// [`MessageToPublish::validate`]), and the usual transport and upstream errors otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(doctest)] {
+    /// use rabbitmq_messages_management::client::ManagementClient;
+    /// use rabbitmq_messages_management::rabbitmq::exchanges::MessageToPublish;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = ManagementClient::new("http://localhost:15672", "guest", "guest").unwrap();
+    ///     let message = MessageToPublish {
+    ///         routing_key: "orders".to_string(),
+    ///         payload: r#"{"id": 1}"#.to_string(),
+    ///         ..Default::default()
+    ///     };
+    ///     let published = client.publish("/", "amq.default", &message).await.unwrap();
+    ///     println!("routed: {}", published.routed);
+    /// }
+    /// # }
+    /// ```
+    pub async fn publish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        message: &MessageToPublish,
+    ) -> Result<PublishResponse, ServerError> {
+        message.validate()?;
+        let request = PublishRequest {
+            properties: message.properties.to_publish()?,
+            routing_key: &message.routing_key,
+            payload: &message.payload,
+            payload_encoding: message.payload_encoding,
+        };
+        let body = serde_json::to_string(&request).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+        self.post(&ApiPath::exchange_publish(vhost, exchange), body)
+            .await
+    }
+}
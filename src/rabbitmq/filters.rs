@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json_path::JsonPath;
+
+use crate::{
+    exceptions::{FieldError, ServerError},
+    pattern::glob_matches,
+};
+
+use super::queues::ResponseForQueryingMessages;
+
+/// Criteria selecting messages. Unset criteria match every message.
+///
+/// A filter is compiled into a [`MessageMatcher`] before use, which validates its regex and JSONPath.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub struct MessageFilter {
+    /// Glob the routing key must match, e.g. `orders.*.eu` (see [`glob_matches`]).
+    pub routing_key: Option<String>,
+    /// Headers the message must carry, with these values. A string also matches a header holding a
+    /// number or boolean written the same way, e.g. `"3"` matches `3`.
+    pub headers: HashMap<String, serde_json::Value>,
+    /// Headers the message must carry, whatever their value.
+    pub has_header: Vec<String>,
+    /// Content type the message must have, ignoring case and parameters such as `charset`.
+    pub content_type: Option<String>,
+    /// Whether the message must have been redelivered, or not.
+    pub redelivered: Option<bool>,
+    /// Text the payload must contain.
+    pub payload_contains: Option<String>,
+    /// Regular expression the payload must match.
+    pub payload_regex: Option<String>,
+    /// JSONPath query that must select at least one node of the (JSON) payload, e.g.
+    /// `$.items[?@.price > 100]`.
+    pub json_path: Option<String>,
+}
+
+impl MessageFilter {
+    /// Indicates if no criteria are set, i.e. every message matches.
+    pub fn is_empty(&self) -> bool {
+        self.routing_key.is_none()
+            && self.headers.is_empty()
+            && self.has_header.is_empty()
+            && self.content_type.is_none()
+            && self.redelivered.is_none()
+            && self.payload_contains.is_none()
+            && self.payload_regex.is_none()
+            && self.json_path.is_none()
+    }
+
+    /// Validates the filter and prepares it for matching messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidParameters` if the regex or the JSONPath query is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::rabbitmq::filters::MessageFilter;
+    /// let filter = MessageFilter {
+    ///     payload_regex: Some("(unclosed".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// assert!(filter.compile().is_err());
+    /// ```
+    pub fn compile(&self) -> Result<MessageMatcher, ServerError> {
+        let mut errors = vec![];
+        let payload_regex =
+            self.payload_regex
+                .as_deref()
+                .and_then(|pattern| match Regex::new(pattern) {
+                    Ok(regex) => Some(regex),
+                    Err(e) => {
+                        errors.push(FieldError {
+                            field: "payload_regex".to_string(),
+                            message: e.to_string(),
+                        });
+                        None
+                    }
+                });
+        let json_path = self
+            .json_path
+            .as_deref()
+            .and_then(|query| match JsonPath::parse(query) {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    errors.push(FieldError {
+                        field: "json_path".to_string(),
+                        message: e.to_string(),
+                    });
+                    None
+                }
+            });
+        if !errors.is_empty() {
+            return Err(ServerError::InvalidParameters { errors });
+        }
+        Ok(MessageMatcher {
+            filter: self.clone(),
+            payload_regex,
+            json_path,
+        })
+    }
+}
+
+/// A validated [`MessageFilter`], ready to match messages.
+#[derive(Debug, Clone)]
+pub struct MessageMatcher {
+    /// The filter the matcher was compiled from.
+    filter: MessageFilter,
+    /// Compiled `payload_regex`.
+    payload_regex: Option<Regex>,
+    /// Parsed `json_path`.
+    json_path: Option<JsonPath>,
+}
+
+impl MessageMatcher {
+    /// Indicates if the filter has payload criteria, which need the content of the messages.
+    pub fn reads_payload(&self) -> bool {
+        self.filter.payload_contains.is_some()
+            || self.payload_regex.is_some()
+            || self.json_path.is_some()
+    }
+
+    /// Indicates if a message meets every criteria.
+    ///
+    /// Payload criteria apply to the content of the payload (see
+    /// [`ResponseForQueryingMessages::content`]): base64 payloads are decoded, compressed payloads are
+    /// matched decompressed if they were decompressed first, and truncated payloads are matched as they
+    /// are. A payload that is not JSON never matches `json_path`.
+    pub fn matches(&self, message: &ResponseForQueryingMessages) -> bool {
+        let filter = &self.filter;
+        let properties = message.properties();
+        let headers = properties.headers();
+
+        let metadata_matches = filter
+            .routing_key
+            .as_deref()
+            .is_none_or(|pattern| glob_matches(pattern, message.routing_key()))
+            && filter.headers.iter().all(|(name, expected)| {
+                headers
+                    .get(name)
+                    .is_some_and(|actual| header_equals(actual, expected))
+            })
+            && filter
+                .has_header
+                .iter()
+                .all(|name| headers.contains_key(name))
+            && filter.content_type.as_deref().is_none_or(|expected| {
+                properties.content_type().is_some_and(|actual| {
+                    mime_essence(actual).eq_ignore_ascii_case(mime_essence(expected))
+                })
+            })
+            && filter
+                .redelivered
+                .is_none_or(|redelivered| message.redelivered() == redelivered);
+        if !metadata_matches {
+            return false;
+        }
+        if !self.reads_payload() {
+            return true;
+        }
+
+        let Some(payload) = message.content() else {
+            return false;
+        };
+        filter
+            .payload_contains
+            .as_deref()
+            .is_none_or(|text| contains(&payload, text.as_bytes()))
+            && self
+                .payload_regex
+                .as_ref()
+                .is_none_or(|regex| regex.is_match(&payload))
+            && self.json_path.as_ref().is_none_or(|path| {
+                serde_json::from_slice::<serde_json::Value>(&payload)
+                    .is_ok_and(|value| !path.query(&value).is_empty())
+            })
+    }
+}
+
+/// Compares a header to the value a filter expects.
+fn header_equals(actual: &serde_json::Value, expected: &serde_json::Value) -> bool {
+    match (actual, expected) {
+        (serde_json::Value::String(_), _) => actual == expected,
+        (_, serde_json::Value::String(text)) => {
+            serde_json::from_str::<serde_json::Value>(text).is_ok_and(|value| value == *actual)
+        }
+        _ => actual == expected,
+    }
+}
+
+/// The type and subtype of a MIME type, without its parameters.
+fn mime_essence(mime: &str) -> &str {
+    mime.split(';').next().unwrap_or_default().trim()
+}
+
+/// Indicates if `needle` appears in `haystack`.
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty()
+        || haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+}
@@ -1,2 +1,9 @@
+pub mod content;
+pub mod exchanges;
+pub mod filters;
+pub mod moves;
+pub mod path;
 pub mod queues;
+pub mod scan;
+pub mod users;
 pub mod vhosts;
@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    client::ManagementClient,
+    exceptions::{ErrorBody, FieldError, ServerError},
+};
+
+use super::{
+    exchanges::{MessageToPublish, DEFAULT_EXCHANGE},
+    filters::{MessageFilter, MessageMatcher},
+    queues::{AckMode, MessageRetrievalOptions, PayloadEncoding, ResponseForQueryingMessages},
+};
+
+/// Maximum number of messages moved by one request.
+pub const MAX_MOVE_COUNT: u64 = 1000;
+
+/// Where moved messages are published.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum MoveDestination {
+    /// The queue of this name, through the default exchange: `{"queue": "orders"}`.
+    Queue(String),
+    /// The exchange and routing key each message was dead-lettered from, read from its `x-death`
+    /// header: `"dead_letter_origin"`.
+    DeadLetterOrigin,
+}
+
+/// A request to move messages from the head of a queue to another destination.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(crate = "rocket::serde")]
+pub struct MoveRequest {
+    /// Maximum number of messages to move, up to [`MAX_MOVE_COUNT`].
+    pub count: u64,
+    /// Where the messages are published.
+    pub destination: MoveDestination,
+    /// Only messages matching the filter are moved: the move stops at the first message that does not
+    /// match, which is left at the head of the queue.
+    #[serde(default)]
+    pub filter: MessageFilter,
+}
+
+impl MoveRequest {
+    /// Checks the request before any message is touched.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidParameters` if the count is out of range, the destination is the
+    /// source queue itself or the filter is invalid.
+    pub fn validate(&self, queue_name: &str) -> Result<MessageMatcher, ServerError> {
+        let matcher = self.filter.compile()?;
+        let mut errors = vec![];
+        if !(1..=MAX_MOVE_COUNT).contains(&self.count) {
+            errors.push(FieldError {
+                field: "count".to_string(),
+                message: format!("must be between 1 and {}", MAX_MOVE_COUNT),
+            });
+        }
+        if self.destination == MoveDestination::Queue(queue_name.to_string()) {
+            errors.push(FieldError {
+                field: "destination".to_string(),
+                message: "must not be the queue the messages are moved from".to_string(),
+            });
+        }
+        if errors.is_empty() {
+            Ok(matcher)
+        } else {
+            Err(ServerError::InvalidParameters { errors })
+        }
+    }
+}
+
+/// A message that was published to the destination and then removed from the source queue.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct MovedMessage {
+    /// Application message identifier, if set.
+    pub message_id: Option<String>,
+    /// Exchange the message was published to.
+    pub exchange: String,
+    /// Routing key the message was published with.
+    pub routing_key: String,
+}
+
+/// Exact account of what a move did, including when it stopped early.
+#[derive(Serialize, Debug, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct MoveReport {
+    /// Messages moved, in the order they were moved.
+    pub moved: Vec<MovedMessage>,
+    /// Whether the move stopped on a message that does not match the filter, left at the head of the
+    /// source queue.
+    pub unmatched: bool,
+    /// Messages examined.
+    pub scanned: u64,
+    /// Error the move stopped on, if it did not run to completion.
+    #[serde(serialize_with = "error_body")]
+    pub error: Option<ServerError>,
+}
+
+/// Serializes the error of a report like the API renders errors.
+pub(crate) fn error_body<S>(error: &Option<ServerError>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    error.as_ref().map(ErrorBody::from).serialize(serializer)
+}
+
+/// The exchange and routing key a message was last dead-lettered from, read from its `x-death` header.
+///
+/// The default exchange (an empty name in `x-death`) is returned as [`DEFAULT_EXCHANGE`].
+pub fn dead_letter_origin(message: &ResponseForQueryingMessages) -> Option<(String, String)> {
+    // The broker keeps the most recent death first.
+    let death = message
+        .properties()
+        .headers()
+        .get("x-death")?
+        .as_array()?
+        .first()?;
+    let exchange = death.get("exchange")?.as_str()?;
+    let routing_key = death.get("routing-keys")?.as_array()?.first()?.as_str()?;
+    let exchange = if exchange.is_empty() {
+        DEFAULT_EXCHANGE
+    } else {
+        exchange
+    };
+    Some((exchange.to_string(), routing_key.to_string()))
+}
+
+impl ManagementClient {
+    /// Moves up to `request.count` messages from the head of a queue to the requested destination,
+    /// e.g. to replay dead-lettered messages.
+    ///
+    /// The management API can only take messages from the head of a queue, so messages are handled one
+    /// at a time: the head message is fetched (and requeued), published to the destination, and only
+    /// then removed from the queue, after checking that the removed message is the one that was
+    /// published. Messages are never republished to the source queue, which would change their
+    /// exchange, routing key and position, so the move stops at the first message that does not match
+    /// the filter and leaves it at the head of the queue (see [`MoveReport::unmatched`]).
+    ///
+    /// The move stops on the first failure, leaving the message it was handling in the queue: when
+    /// publishing fails, when the destination does not route the message, when a message to restore
+    /// has no `x-death` header, or when the head of the queue changed under a concurrent consumer.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidParameters` if the request is invalid. Failures once messages are
+    /// being moved are returned in [`MoveReport::error`], together with what was moved before them.
+    pub async fn move_messages(
+        &self,
+        vhost: &str,
+        queue_name: &str,
+        request: &MoveRequest,
+    ) -> Result<MoveReport, ServerError> {
+        let matcher = request.validate(queue_name)?;
+        let mut report = MoveReport::default();
+        if let Err(error) = self
+            .move_into_report(vhost, queue_name, request, &matcher, &mut report)
+            .await
+        {
+            report.error = Some(error);
+        }
+        Ok(report)
+    }
+
+    /// Moves messages, recording each one in `report` as soon as it has been moved.
+    async fn move_into_report(
+        &self,
+        vhost: &str,
+        queue_name: &str,
+        request: &MoveRequest,
+        matcher: &MessageMatcher,
+        report: &mut MoveReport,
+    ) -> Result<(), ServerError> {
+        while (report.moved.len() as u64) < request.count {
+            let Some(head) = self
+                .fetch_head(vhost, queue_name, AckMode::AckRequeueTrue)
+                .await?
+            else {
+                break;
+            };
+            report.scanned += 1;
+            if !matcher.matches(&head) {
+                report.unmatched = true;
+                break;
+            }
+
+            let (exchange, routing_key) = match &request.destination {
+                MoveDestination::Queue(queue) => (DEFAULT_EXCHANGE.to_string(), queue.clone()),
+                MoveDestination::DeadLetterOrigin => {
+                    dead_letter_origin(&head).ok_or_else(|| ServerError::Conflict {
+                        reason: "the head message has no x-death header to restore its exchange \
+                                 and routing key from; it was left in the queue"
+                            .to_string(),
+                    })?
+                }
+            };
+            self.republish(vhost, &exchange, &routing_key, &head)
+                .await?;
+            self.remove_head(vhost, queue_name, &head, "published to the destination")
+                .await?;
+            report.moved.push(MovedMessage {
+                message_id: head.properties().message_id().map(str::to_string),
+                exchange,
+                routing_key,
+            });
+        }
+        Ok(())
+    }
+
+    /// Fetches the message at the head of a queue, base64 encoded so binary payloads survive.
+    async fn fetch_head(
+        &self,
+        vhost: &str,
+        queue_name: &str,
+        ackmode: AckMode,
+    ) -> Result<Option<ResponseForQueryingMessages>, ServerError> {
+        let options = MessageRetrievalOptions {
+            count: 1,
+            ackmode,
+            encoding: PayloadEncoding::Base64,
+            truncate: None,
+        };
+        Ok(self
+            .get_messages_from_a_queue(vhost.to_string(), queue_name.to_string(), &options)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    /// Publishes a copy of a fetched message, failing if no queue received it.
+    async fn republish(
+        &self,
+        vhost: &str,
+        exchange: &str,
+        routing_key: &str,
+        message: &ResponseForQueryingMessages,
+    ) -> Result<(), ServerError> {
+        let published = self
+            .publish(
+                vhost,
+                exchange,
+                &MessageToPublish::from_message(message, routing_key),
+            )
+            .await?;
+        if !published.routed {
+            return Err(ServerError::Conflict {
+                reason: format!(
+                    "exchange {:?} routed the head message with routing key {:?} to no queue; it \
+                     was left in the queue",
+                    exchange, routing_key
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Removes the head message of a queue once a copy of `expected` has been published.
+    ///
+    /// If a concurrent consumer took `expected` in the meantime, whatever was removed instead is
+    /// published back to the queue and the move is stopped.
+    async fn remove_head(
+        &self,
+        vhost: &str,
+        queue_name: &str,
+        expected: &ResponseForQueryingMessages,
+        published: &str,
+    ) -> Result<(), ServerError> {
+        let changed = |outcome: &str| ServerError::Conflict {
+            reason: format!(
+                "the head of the queue changed while moving: the message was {} but {}, so it may \
+                 now be duplicated",
+                published, outcome
+            ),
+        };
+        let Some(removed) = self
+            .fetch_head(vhost, queue_name, AckMode::AckRequeueFalse)
+            .await?
+        else {
+            return Err(changed("had already been consumed"));
+        };
+        if removed.fingerprint() == expected.fingerprint() {
+            return Ok(());
+        }
+        self.republish(vhost, DEFAULT_EXCHANGE, queue_name, &removed)
+            .await?;
+        Err(changed(
+            "another message was at the head of the queue; that one was put back at the back of the queue",
+        ))
+    }
+}
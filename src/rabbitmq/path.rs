@@ -0,0 +1,91 @@
+use core::fmt;
+
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// Characters that are percent-encoded in a management API path segment.
+///
+/// Everything except the RFC 3986 unreserved characters is encoded, so that a vhost or queue name can
+/// never be mistaken for a path separator (`/`), a query (`?`) or a fragment (`#`).
+const SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// A path on the RabbitMQ management API, relative to the management root.
+///
+/// Each segment is percent-encoded on its own, so names such as the default vhost `/` end up as a
+/// single `%2F` segment instead of splitting the path.
+///
+/// # Example
+///
+/// ```rust
+/// use rabbitmq_messages_management::rabbitmq::path::ApiPath;
+/// assert_eq!(ApiPath::queue_get("/", "orders #1").to_string(), "api/queues/%2F/orders%20%231/get");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiPath {
+    /// Encoded segments of the path.
+    segments: Vec<String>,
+}
+
+impl ApiPath {
+    /// Creates the `api` root path.
+    pub fn api() -> ApiPath {
+        ApiPath {
+            segments: vec!["api".to_string()],
+        }
+    }
+
+    /// Appends a segment, percent-encoding it.
+    pub fn segment(mut self, segment: &str) -> ApiPath {
+        self.segments
+            .push(utf8_percent_encode(segment, SEGMENT).to_string());
+        self
+    }
+
+    /// Path describing the authenticated user: `api/whoami`.
+    pub fn whoami() -> ApiPath {
+        ApiPath::api().segment("whoami")
+    }
+
+    /// Path listing all virtual hosts: `api/vhosts`.
+    pub fn vhosts() -> ApiPath {
+        ApiPath::api().segment("vhosts")
+    }
+
+    /// Path used to publish to an exchange: `api/exchanges/<vhost>/<exchange>/publish`.
+    pub fn exchange_publish(vhost: &str, exchange: &str) -> ApiPath {
+        ApiPath::api()
+            .segment("exchanges")
+            .segment(vhost)
+            .segment(exchange)
+            .segment("publish")
+    }
+
+    /// Path listing the queues of a virtual host: `api/queues/<vhost>`.
+    pub fn queues(vhost: &str) -> ApiPath {
+        ApiPath::api().segment("queues").segment(vhost)
+    }
+
+    /// Path of a single queue: `api/queues/<vhost>/<queue>`.
+    pub fn queue(vhost: &str, queue_name: &str) -> ApiPath {
+        ApiPath::queues(vhost).segment(queue_name)
+    }
+
+    /// Path of the messages of a queue, deleted to purge it: `api/queues/<vhost>/<queue>/contents`.
+    pub fn queue_contents(vhost: &str, queue_name: &str) -> ApiPath {
+        ApiPath::queue(vhost, queue_name).segment("contents")
+    }
+
+    /// Path used to get messages from a queue: `api/queues/<vhost>/<queue>/get`.
+    pub fn queue_get(vhost: &str, queue_name: &str) -> ApiPath {
+        ApiPath::queue(vhost, queue_name).segment("get")
+    }
+}
+
+impl fmt::Display for ApiPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.segments.join("/"))
+    }
+}
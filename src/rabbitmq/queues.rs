@@ -1,109 +1,375 @@
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use base64::prelude::*;
 
-use crate::constants::RABBITMQ_MANAGEMENT_ROOT;
-use rabbitmq_messages_management::{
-    exceptions::ServerError, prepare_authorization_headers, prepare_url, send_get, send_post,
+use crate::{client::ManagementClient, exceptions::ServerError};
+
+use super::{
+    content::{ContentEncoding, DecodeBudget, Decompressed},
+    filters::MessageMatcher,
+    path::ApiPath,
 };
-use serde::{Deserialize, Serialize};
+use rocket::FromFormField;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
 
-// Represents a RabbitMQ queue.
+/// Represents a RabbitMQ queue.
+///
+/// Only the fields shared by every queue type live here; the type-specific fields are in
+/// [`QueueType`], selected by the `type` field returned by the management API. Fields that a given
+/// broker version does not return fall back to their defaults.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "rocket::serde")]
-pub(crate) struct Queue {
+pub struct Queue {
     /// Arguments for the queue.
+    #[serde(default)]
     arguments: Arguments,
     /// Indicates if the queue is auto-deleted when no longer used.
+    #[serde(default)]
     auto_delete: bool,
-    /// Status of the backing queue.
-    backing_queue_status: BackingQueueStatus,
     /// Capacity of the consumer.
+    #[serde(default)]
     consumer_capacity: f64,
     /// Utilization of the consumer.
+    #[serde(default)]
     consumer_utilisation: f64,
     /// Number of consumers.
+    #[serde(default)]
     consumers: u64,
     /// Indicates if the queue is durable.
+    #[serde(default)]
     durable: bool,
     /// Effective policy definition for the queue.
+    #[serde(default)]
     effective_policy_definition: EffectivePolicyDefinition,
     /// Indicates if the queue is exclusive.
+    #[serde(default)]
     exclusive: bool,
     /// Tag of the exclusive consumer, if any.
+    #[serde(default)]
     exclusive_consumer_tag: Option<String>,
     /// Garbage collection settings for the queue.
-    garbage_collection: GarbageCollection,
-    /// Timestamp of the head message, if any.
-    head_message_timestamp: Option<String>,
-    /// Time since the queue has been idle.
-    idle_since: String,
+    #[serde(default)]
+    garbage_collection: Option<GarbageCollection>,
     /// Memory used by the queue.
+    #[serde(default)]
     memory: u64,
     /// Total bytes of messages in the queue.
+    #[serde(default)]
     message_bytes: u64,
-    /// Bytes of messages paged out.
-    message_bytes_paged_out: u64,
-    /// Bytes of persistent messages.
-    message_bytes_persistent: u64,
-    /// Bytes of messages in RAM.
-    message_bytes_ram: u64,
     /// Bytes of ready messages.
+    #[serde(default)]
     message_bytes_ready: u64,
     /// Bytes of unacknowledged messages.
+    #[serde(default)]
     message_bytes_unacknowledged: u64,
     /// Total number of messages in the queue.
+    #[serde(default)]
     messages: u64,
     /// Details of the messages.
+    #[serde(default)]
     messages_details: MessageDetails,
-    /// Number of messages paged out.
-    messages_paged_out: u64,
-    /// Number of persistent messages.
-    messages_persistent: u64,
-    /// Number of messages in RAM.
-    messages_ram: u64,
     /// Number of ready messages.
+    #[serde(default)]
     messages_ready: u64,
     /// Details of the ready messages.
+    #[serde(default)]
     messages_ready_details: MessageDetails,
-    /// Number of ready messages in RAM.
-    messages_ready_ram: u64,
     /// Number of unacknowledged messages.
+    #[serde(default)]
     messages_unacknowledged: u64,
     /// Details of the unacknowledged messages.
+    #[serde(default)]
     messages_unacknowledged_details: MessageDetails,
-    /// Number of unacknowledged messages in RAM.
-    messages_unacknowledged_ram: u64,
     /// Name of the queue.
     name: String,
-    /// Node where the queue is located.
-    node: String,
+    /// Node where the queue (or its leader) is located.
+    #[serde(default)]
+    node: Option<String>,
     /// Operator policy for the queue, if any.
+    #[serde(default)]
     operator_policy: Option<String>,
     /// Policy for the queue, if any.
+    #[serde(default)]
     policy: Option<String>,
-    /// Recoverable slaves for the queue, if any.
-    recoverable_slaves: Option<String>,
     /// Number of reductions.
+    #[serde(default)]
     reductions: u64,
     /// Details of the reductions.
+    #[serde(default)]
     reductions_details: ReductionsDetails,
     /// Tag of the single active consumer, if any.
+    #[serde(default)]
     single_active_consumer_tag: Option<String>,
     /// State of the queue.
-    state: String,
-    /// Type of the queue.
-    #[serde(rename = "type")]
-    queue_type: String,
+    #[serde(default)]
+    state: Option<String>,
+    /// Type of the queue, together with the fields specific to that type.
+    #[serde(flatten)]
+    queue_type: QueueType,
     /// Virtual host of the queue.
     vhost: String,
 }
 
-/// Represents the arguments for a RabbitMQ queue.
+impl Queue {
+    /// Name of the queue.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Virtual host of the queue.
+    pub fn vhost(&self) -> &str {
+        &self.vhost
+    }
+
+    /// Total number of messages in the queue.
+    pub fn messages(&self) -> u64 {
+        self.messages
+    }
+
+    /// Number of messages ready to be delivered, i.e. the ones a purge removes.
+    pub fn messages_ready(&self) -> u64 {
+        self.messages_ready
+    }
+
+    /// Number of consumers.
+    pub fn consumers(&self) -> u64 {
+        self.consumers
+    }
+
+    /// Type of the queue, together with the fields specific to that type.
+    pub fn queue_type(&self) -> &QueueType {
+        &self.queue_type
+    }
+
+    /// Number of redeliveries after which a quorum queue drops or dead-letters a message, if limited.
+    pub fn delivery_limit(&self) -> Option<u64> {
+        match &self.queue_type {
+            QueueType::Quorum(quorum) => quorum.delivery_limit.or_else(|| {
+                // Brokers before 4.0 only return the limits set by argument and policy; the lowest applies.
+                [
+                    self.arguments.delivery_limit,
+                    self.effective_policy_definition.delivery_limit,
+                ]
+                .into_iter()
+                .flatten()
+                .min()
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Type of a RabbitMQ queue, as returned in the `type` field, with its type-specific fields.
 #[derive(Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
-pub(crate) struct Arguments {}
+#[serde(crate = "rocket::serde", tag = "type", rename_all = "lowercase")]
+pub enum QueueType {
+    /// Classic (non-replicated) queue.
+    Classic(ClassicQueue),
+    /// Raft-based replicated queue.
+    Quorum(QuorumQueue),
+    /// Append-only replicated log.
+    Stream(StreamQueue),
+    /// Queue type this application does not know about (e.g. plugin-provided types).
+    #[serde(other)]
+    Unknown,
+}
+
+/// Fields specific to classic queues.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub struct ClassicQueue {
+    /// Status of the backing queue.
+    backing_queue_status: Option<BackingQueueStatus>,
+    /// Timestamp of the head message, if any.
+    #[serde(deserialize_with = "lenient")]
+    head_message_timestamp: Option<u64>,
+    /// Time since the queue has been idle.
+    idle_since: Option<String>,
+    /// Bytes of messages paged out.
+    message_bytes_paged_out: u64,
+    /// Bytes of persistent messages.
+    message_bytes_persistent: u64,
+    /// Bytes of messages in RAM.
+    message_bytes_ram: u64,
+    /// Number of messages paged out.
+    messages_paged_out: u64,
+    /// Number of persistent messages.
+    messages_persistent: u64,
+    /// Number of messages in RAM.
+    messages_ram: u64,
+    /// Number of ready messages in RAM.
+    messages_ready_ram: u64,
+    /// Number of unacknowledged messages in RAM.
+    messages_unacknowledged_ram: u64,
+    /// Recoverable slaves for the queue, if any.
+    #[serde(deserialize_with = "lenient")]
+    recoverable_slaves: Option<Vec<String>>,
+}
+
+/// Fields specific to quorum queues.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub struct QuorumQueue {
+    /// Node hosting the current leader.
+    leader: Option<String>,
+    /// Nodes hosting a member of the queue.
+    members: Vec<String>,
+    /// Members that are currently online.
+    online: Vec<String>,
+    /// Number of redeliveries after which a message is dropped or dead-lettered, if limited.
+    #[serde(deserialize_with = "lenient")]
+    delivery_limit: Option<u64>,
+    /// Number of messages held for at-least-once dead-lettering.
+    messages_dlx: u64,
+    /// Bytes of messages held for at-least-once dead-lettering.
+    message_bytes_dlx: u64,
+    /// Open segment files per node.
+    open_files: HashMap<String, u64>,
+}
+
+/// Fields specific to stream queues.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub struct StreamQueue {
+    /// Node hosting the current leader.
+    leader: Option<String>,
+    /// Nodes hosting a member of the stream.
+    members: Vec<String>,
+    /// Members that are currently online.
+    online: Vec<String>,
+    /// Number of segment files of the stream.
+    #[serde(deserialize_with = "lenient")]
+    segments: Option<u64>,
+}
+
+/// Deserializes an optional field, falling back to `None` if the broker returned it in an unexpected
+/// shape (e.g. `"infinity"` or `""` where a number is expected).
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: for<'a> Deserialize<'a>,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).ok())
+}
+
+/// Represents the arguments (`x-*`) a RabbitMQ queue was declared with.
+///
+/// Well-known arguments are typed; anything else is preserved as-is in `other`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub(crate) struct Arguments {
+    /// Per-message time to live, in milliseconds.
+    #[serde(
+        rename = "x-message-ttl",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    message_ttl: Option<u64>,
+    /// Time after which an unused queue is deleted, in milliseconds.
+    #[serde(
+        rename = "x-expires",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    expires: Option<u64>,
+    /// Exchange dead-lettered messages are republished to.
+    #[serde(
+        rename = "x-dead-letter-exchange",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dead_letter_exchange: Option<String>,
+    /// Routing key used when dead-lettering, instead of the original one.
+    #[serde(
+        rename = "x-dead-letter-routing-key",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dead_letter_routing_key: Option<String>,
+    /// Dead-lettering guarantees (quorum queues only).
+    #[serde(
+        rename = "x-dead-letter-strategy",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dead_letter_strategy: Option<DeadLetterStrategy>,
+    /// Maximum number of ready messages.
+    #[serde(
+        rename = "x-max-length",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_length: Option<u64>,
+    /// Maximum total body size of ready messages, in bytes.
+    #[serde(
+        rename = "x-max-length-bytes",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_length_bytes: Option<u64>,
+    /// Behaviour when the maximum length is reached.
+    #[serde(
+        rename = "x-overflow",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    overflow: Option<Overflow>,
+    /// Queue mode (classic queues only).
+    #[serde(
+        rename = "x-queue-mode",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    queue_mode: Option<QueueMode>,
+    /// Queue type requested at declaration.
+    #[serde(
+        rename = "x-queue-type",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    queue_type: Option<String>,
+    /// Indicates if only one consumer at a time receives messages.
+    #[serde(
+        rename = "x-single-active-consumer",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    single_active_consumer: Option<bool>,
+    /// Number of redeliveries after which a message is dropped or dead-lettered (quorum queues only).
+    #[serde(
+        rename = "x-delivery-limit",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    delivery_limit: Option<u64>,
+    /// Maximum priority supported by the queue.
+    #[serde(
+        rename = "x-max-priority",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_priority: Option<u8>,
+    /// Maximum age of messages in a stream, e.g. `7D`.
+    #[serde(
+        rename = "x-max-age",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_age: Option<String>,
+    /// Arguments that are not modelled above.
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
+}
 
 /// Represents the status of the backing queue.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
 pub(crate) struct BackingQueueStatus {
     /// Average egress rate for acknowledgments.
     avg_ack_egress_rate: f64,
@@ -119,7 +385,7 @@ pub(crate) struct BackingQueueStatus {
     /// Length of the queue.
     len: u64,
     /// Mode of the queue.
-    mode: String,
+    mode: Option<String>,
     /// Next sequence ID.
     next_seq_id: u64,
     /// Q1 value.
@@ -130,18 +396,134 @@ pub(crate) struct BackingQueueStatus {
     q3: u64,
     /// Q4 value.
     q4: u64,
-    /// Target RAM count.
-    target_ram_count: String,
+    /// Target RAM count (`None` when unlimited).
+    #[serde(deserialize_with = "lenient")]
+    target_ram_count: Option<u64>,
 }
 
 /// Represents the effective policy definition for a RabbitMQ queue.
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
-pub(crate) struct EffectivePolicyDefinition {}
+///
+/// Well-known policy keys are typed; anything else is preserved as-is in `other`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
+pub(crate) struct EffectivePolicyDefinition {
+    /// Per-message time to live, in milliseconds.
+    #[serde(
+        rename = "message-ttl",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    message_ttl: Option<u64>,
+    /// Time after which an unused queue is deleted, in milliseconds.
+    #[serde(
+        rename = "expires",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    expires: Option<u64>,
+    /// Exchange dead-lettered messages are republished to.
+    #[serde(
+        rename = "dead-letter-exchange",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dead_letter_exchange: Option<String>,
+    /// Routing key used when dead-lettering, instead of the original one.
+    #[serde(
+        rename = "dead-letter-routing-key",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dead_letter_routing_key: Option<String>,
+    /// Dead-lettering guarantees (quorum queues only).
+    #[serde(
+        rename = "dead-letter-strategy",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    dead_letter_strategy: Option<DeadLetterStrategy>,
+    /// Maximum number of ready messages.
+    #[serde(
+        rename = "max-length",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_length: Option<u64>,
+    /// Maximum total body size of ready messages, in bytes.
+    #[serde(
+        rename = "max-length-bytes",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_length_bytes: Option<u64>,
+    /// Behaviour when the maximum length is reached.
+    #[serde(
+        rename = "overflow",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    overflow: Option<Overflow>,
+    /// Queue mode (classic queues only).
+    #[serde(
+        rename = "queue-mode",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    queue_mode: Option<QueueMode>,
+    /// Number of redeliveries after which a message is dropped or dead-lettered (quorum queues only).
+    #[serde(
+        rename = "delivery-limit",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    delivery_limit: Option<u64>,
+    /// Maximum age of messages in a stream, e.g. `7D`.
+    #[serde(
+        rename = "max-age",
+        deserialize_with = "lenient",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_age: Option<String>,
+    /// Policy keys that are not modelled above.
+    #[serde(flatten)]
+    other: HashMap<String, serde_json::Value>,
+}
+
+/// Dead-lettering guarantees of a quorum queue.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "kebab-case")]
+pub enum DeadLetterStrategy {
+    /// Messages may be lost while being dead-lettered (the default).
+    AtMostOnce,
+    /// Messages are retained until the dead-letter target confirms them.
+    AtLeastOnce,
+}
+
+/// Behaviour of a queue once its maximum length is reached.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "kebab-case")]
+pub enum Overflow {
+    /// The oldest messages are dropped or dead-lettered (the default).
+    DropHead,
+    /// New publishes are rejected.
+    RejectPublish,
+    /// New publishes are rejected and dead-lettered.
+    RejectPublishDlx,
+}
+
+/// Mode of a classic queue.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "kebab-case")]
+pub enum QueueMode {
+    /// Messages are kept in memory where possible.
+    Default,
+    /// Messages are moved to disk as early as possible.
+    Lazy,
+}
 
 /// Represents the garbage collection settings for a RabbitMQ queue.
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
 pub(crate) struct GarbageCollection {
     /// Full sweep after this many collections.
     fullsweep_after: u64,
@@ -156,16 +538,16 @@ pub(crate) struct GarbageCollection {
 }
 
 /// Represents the details of messages in a RabbitMQ queue.
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
 pub(crate) struct MessageDetails {
     /// Rate of messages.
     rate: f64,
 }
 
 /// Represents the details of reductions in a RabbitMQ queue.
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(crate = "rocket::serde", default)]
 pub(crate) struct ReductionsDetails {
     /// Rate of reductions.
     rate: f64,
@@ -179,30 +561,206 @@ pub(crate) struct MessageRetrievalRequest {
     vhost: String,
     /// Name of the queue
     name: String,
-    /// For requeuing, do `ack_requeue_true`
-    ackmode: String,
-    /// Must be either "auto" (in which case the payload will be returned as a string if it is valid UTF-8,
-    ///  and base64 encoded otherwise), or "base64" (in which case the payload will always be base64 encoded).
-    encoding: String,
+    /// What happens to the messages once they have been fetched
+    ackmode: AckMode,
+    /// How the payload is encoded in the response
+    encoding: PayloadEncoding,
     /// Controls the maximum number of messages to get.
     ///  You may get fewer messages than this if the queue cannot immediately provide them.
     count: u64,
+    /// If set, payloads larger than this many bytes are truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncate: Option<u64>,
 }
 
-/// Represents the properties of a RabbitMQ message.
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(crate = "rocket::serde")]
+/// What the broker does with messages fetched through the management API.
+///
+/// Only [`AckMode::AckRequeueTrue`] and [`AckMode::RejectRequeueTrue`] leave the queue intact; the other
+/// modes remove the fetched messages for good (see [`AckMode::is_destructive`]).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum AckMode {
+    /// Acknowledge and requeue: the messages stay in the queue (flagged as redelivered).
+    #[default]
+    #[field(value = "ack_requeue_true")]
+    AckRequeueTrue,
+    /// Acknowledge without requeueing: the messages are removed from the queue.
+    #[field(value = "ack_requeue_false")]
+    AckRequeueFalse,
+    /// Reject and requeue: the messages stay in the queue (flagged as redelivered).
+    #[field(value = "reject_requeue_true")]
+    RejectRequeueTrue,
+    /// Reject without requeueing: the messages are removed, and dead-lettered if the queue has a DLX.
+    #[field(value = "reject_requeue_false")]
+    RejectRequeueFalse,
+}
+
+impl AckMode {
+    /// Indicates if fetching messages with this mode removes them from the queue.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rabbitmq_messages_management::rabbitmq::queues::AckMode;
+    /// assert!(!AckMode::AckRequeueTrue.is_destructive());
+    /// assert!(AckMode::AckRequeueFalse.is_destructive());
+    /// ```
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, AckMode::AckRequeueFalse | AckMode::RejectRequeueFalse)
+    }
+}
+
+/// How the management API encodes message payloads.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, FromFormField)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum PayloadEncoding {
+    /// Payloads are returned as strings if they are valid UTF-8, and base64 encoded otherwise.
+    #[default]
+    Auto,
+    /// Payloads are always base64 encoded.
+    Base64,
+}
+
+/// Options controlling how messages are fetched from a queue.
+#[derive(Debug, Clone, Default)]
+pub struct MessageRetrievalOptions {
+    /// Maximum number of messages to get.
+    pub count: u64,
+    /// What happens to the messages once they have been fetched.
+    pub ackmode: AckMode,
+    /// How the payload is encoded in the response.
+    pub encoding: PayloadEncoding,
+    /// If set, payloads larger than this many bytes are truncated.
+    pub truncate: Option<u64>,
+}
+
+/// Represents the AMQP 0-9-1 basic properties of a RabbitMQ message.
+///
+/// Every property is optional, as the broker only returns the ones the publisher set.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(crate = "rocket::serde", default)]
 pub struct Properties {
+    /// MIME content type of the payload.
+    content_type: Option<String>,
+    /// MIME content encoding of the payload, e.g. `gzip`.
+    content_encoding: Option<String>,
+    /// Headers associated with the message.
+    headers: HashMap<String, serde_json::Value>,
+    /// Delivery mode of the message (1 = transient, 2 = persistent).
+    delivery_mode: Option<u8>,
     /// Priority of the message.
     priority: Option<u8>,
-    /// Delivery mode of the message.
-    delivery_mode: u8,
+    /// Application correlation identifier.
+    correlation_id: Option<String>,
+    /// Address to reply to.
+    reply_to: Option<String>,
+    /// Per-message time to live, in milliseconds.
+    expiration: Option<String>,
+    /// Application message identifier.
+    message_id: Option<String>,
+    /// Time the message was published, output as RFC 3339.
+    timestamp: Option<Timestamp>,
+    /// Application message type.
+    #[serde(rename = "type")]
+    message_type: Option<String>,
+    /// Identifier of the user that published the message.
+    user_id: Option<String>,
+    /// Identifier of the publishing application.
+    app_id: Option<String>,
+    /// Deprecated cluster identifier.
+    cluster_id: Option<String>,
+}
+
+impl Properties {
     /// Headers associated with the message.
-    headers: HashMap<String, serde_json::Value>,
-    /// Content type of the message.
-    content_type: Option<String>,
+    pub fn headers(&self) -> &HashMap<String, serde_json::Value> {
+        &self.headers
+    }
+
+    /// MIME content type of the payload, if set.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// MIME content encoding of the payload, if set.
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.content_encoding.as_deref()
+    }
+
+    /// Application message identifier, if set.
+    pub fn message_id(&self) -> Option<&str> {
+        self.message_id.as_deref()
+    }
+
+    /// Properties in the shape the management API publishes them with: unset properties are left out
+    /// and the timestamp is given in seconds since the Unix epoch.
+    pub fn to_publish(&self) -> Result<serde_json::Map<String, serde_json::Value>, ServerError> {
+        let value = serde_json::to_value(self).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+        let serde_json::Value::Object(mut properties) = value else {
+            return Ok(serde_json::Map::new());
+        };
+        properties.retain(|_, value| !value.is_null());
+        if let Some(timestamp) = self.timestamp {
+            properties.insert("timestamp".to_string(), timestamp.unix_seconds().into());
+        }
+        Ok(properties)
+    }
 }
 
+/// AMQP timestamp (seconds since the Unix epoch).
+///
+/// The management API returns timestamps as integers; they are serialized as RFC 3339 strings. Both
+/// representations are accepted when deserializing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Seconds since the Unix epoch.
+    pub fn unix_seconds(&self) -> i64 {
+        self.0
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let formatted = OffsetDateTime::from_unix_timestamp(self.0)
+            .map_err(serde::ser::Error::custom)?
+            .format(&Rfc3339)
+            .map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&formatted)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Number(number) => number
+                .as_i64()
+                .map(Timestamp)
+                .ok_or_else(|| serde::de::Error::custom("timestamp out of range")),
+            serde_json::Value::String(text) => OffsetDateTime::parse(&text, &Rfc3339)
+                .map(|datetime| Timestamp(datetime.unix_timestamp()))
+                .map_err(serde::de::Error::custom),
+            other => Err(serde::de::Error::custom(format!(
+                "expected a timestamp, found {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Headers the broker adds to or updates in a message each time it is delivered, e.g. when a quorum
+/// queue requeues a message fetched with [`AckMode::AckRequeueTrue`].
+pub const DELIVERY_HEADERS: [&str; 2] = ["x-delivery-count", "x-acquired-count"];
+
 /// Represents a RabbitMQ message.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "rocket::serde")]
@@ -226,137 +784,445 @@ pub struct RabbitMQMessage {
 }
 
 /// API response for querying messages from a queue.
-#[derive(Serialize, Deserialize, Debug)]
+///
+/// Carries the payload together with the routing information and properties of the message, so that
+/// routing problems can be debugged from the response alone.
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(crate = "rocket::serde")]
 pub struct ResponseForQueryingMessages {
     /// Payload from a queue
     payload: String,
     /// Encoding of the payload
     payload_encoding: String,
+    /// Number of bytes in the payload
+    payload_bytes: u64,
+    /// Exchange the message was published to
+    exchange: String,
+    /// Routing key the message was published with
+    routing_key: String,
+    /// Indicates if the message was redelivered
+    redelivered: bool,
+    /// Number of messages remaining in the queue after this one was fetched
+    message_count: u64,
+    /// Properties of the message, including its headers
+    properties: Properties,
+    /// How the payload was decompressed, if it was (see [`ResponseForQueryingMessages::decode_content`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_decoding: Option<ContentDecoding>,
+    /// Outcome of decompressing the payload, once [`ResponseForQueryingMessages::decompress`] did.
+    #[serde(skip)]
+    decompression: Option<Decompression>,
 }
 
-/// Fetches the details of a specific queue for a given virtual host.
-///
-/// This function sends an HTTP GET request to the RabbitMQ management API to retrieve the details
-/// of queues in specified virtual host. The function uses the `send_get` function
-/// to perform the HTTP request and deserializes the response into a vector of `Queue` structs.
-///
-///
-/// # Arguments
-///
-/// - `vhost`: A string slice that holds the name of the virtual host.
-///
-/// # Returns
-///
-/// - `Result<Vec<Queue>, ()>`: On success, returns a vector of `Queue` structs. On failure, returns an empty tuple `()`.
-///
-/// # Example
-///
-/// ```rust
-/// # #[cfg(doctest)] {
-/// #[tokio::main]
-/// async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-///     use rabbitmq_messages_management::rabbitmq::queues::get_queue_for_vhost;
-///     let vhost = "my_vhost";
-///     let queues = get_queue_for_vhost(vhost).await.unwrap();
-///     println!("{:?}", queues);
-///     Ok(())~
-/// }
-/// # }
-/// ```
-pub async fn get_queue_for_vhost(vhost: &str) -> Result<Vec<Queue>, ServerError> {
-    let root = &dotenv::var(RABBITMQ_MANAGEMENT_ROOT).expect("RABBITMQ_MANAGEMENT_ROOT not set");
-    let url = prepare_url(root, &format!("api/queues/{}", vhost)).unwrap();
-    let queues_response: Result<Vec<Queue>, ()> =
-        send_get(&url, Some(&prepare_authorization_headers())).await;
-
-    match queues_response {
-        Ok(queues) => Ok(queues),
-        Err(e) => Err(ServerError::new(format!("{:?}", e))),
-    }
-}
-
-/// Retrieves messages from a specified queue in a given virtual host.
-///
-/// This function sends an HTTP POST request to the RabbitMQ management API to retrieve messages
-/// from a specified queue in a given virtual host. The request includes the virtual host, queue name,
-/// and the number of messages to retrieve. The response is deserialized into a vector of `RabbitMQMessage` structs,
-/// and the payloads of these messages are returned as a vector of `ResponseForQueryingMessages` structs.
-///
-/// # Arguments
-///
-/// * `vhost` - A string representing the virtual host from which to retrieve messages.
-/// * `queue_name` - A string representing the name of the queue from which to retrieve messages.
-/// * `count` - A u64 representing the number of messages to retrieve.
-///
-/// # Returns
-///
-/// * `Result<Vec<ResponseForQueryingMessages>, ()>` - On success, returns a vector of `ResponseForQueryingMessages` structs. On failure, returns an empty tuple `()`.
-///
-/// # Example
-///
-/// ```rust
-/// #[tokio::main]
-/// async fn main() {
-///     let vhost = "my_vhost".to_string();
-///     let queue_name = "my_queue".to_string();
-///     let count = 10;
-///
-///     match get_messages_from_a_queue(vhost, queue_name, count).await {
-///         Ok(messages) => {
-///             for message in messages {
-///                 println!("Payload: {}", message.payload);
-///                 println!("Payload Encoding: {}", message.payload_encoding);
-///                 println!("Content Type: {}", message.content_type);
-///             }
-///         }
-///         Err(_) => println!("Failed to retrieve messages"),
-///     }
-/// }
-/// ```
-pub async fn get_messages_from_a_queue(
-    vhost: String,
-    queue_name: String,
-    count: u64,
-) -> Result<Vec<ResponseForQueryingMessages>, ServerError> {
-    let root = &dotenv::var(RABBITMQ_MANAGEMENT_ROOT).expect("RABBITMQ_MANAGEMENT_ROOT not set");
-    let url = prepare_url(root, &format!("api/queues/{}/{}/get", vhost, queue_name)).unwrap();
-    let request = MessageRetrievalRequest {
-        vhost,
-        name: queue_name,
-        ackmode: "ack_requeue_true".to_string(),
-        encoding: "auto".to_string(),
-        count,
-    };
-    // The following other types implement trait `From<T>`:
-    //  ```
-    //  <isahc::body::AsyncBody as From<&[u8]>>
-    //  <isahc::body::AsyncBody as From<&str>>
-    //  <isahc::body::AsyncBody as From<()>>
-    //  <isahc::body::AsyncBody as From<Vec<u8>>>
-    //  <isahc::body::AsyncBody as From<std::option::Option<T>>>
-    //  <isahc::body::AsyncBody as From<std::string::String>>
-    // ```
-    //
-    // Therefore, if the request body is not of type `&[u8]`, `&str`, `()`, `Vec<u8>`, or `std::string::String`
-    //  make sure you implement it for the request body you're setting.
-    // I suggest use serde_json::to_string to convert the struct to a string and use it as a body.
-
-    let messages_response: Result<Vec<RabbitMQMessage>, ()> = send_post(
-        &url,
-        Some(&prepare_authorization_headers()),
-        serde_json::to_string(&request).unwrap(),
-    )
-    .await;
-
-    match messages_response {
-        Ok(messages) => Ok(messages
-            .iter()
-            .map(|message| ResponseForQueryingMessages {
-                payload: message.payload.clone(),
-                payload_encoding: message.payload_encoding.clone(),
+/// Outcome of decompressing a payload, kept so that it is decompressed once.
+#[derive(Debug, Clone)]
+enum Decompression {
+    /// The payload was decompressed from `encoded_bytes` bytes.
+    Done {
+        /// The content.
+        content: Decompressed,
+        /// Number of bytes of the compressed payload.
+        encoded_bytes: u64,
+    },
+    /// The payload could not be decompressed.
+    Failed,
+}
+
+/// How a payload was decompressed according to its `content_encoding`, and what it was before.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(crate = "rocket::serde")]
+pub struct ContentDecoding {
+    /// Content encoding the payload was decompressed from, e.g. `gzip`.
+    pub content_encoding: String,
+    /// Encoding of the compressed payload, as fetched: `string` or `base64`.
+    pub encoded_payload_encoding: String,
+    /// Number of bytes of the compressed payload that were fetched, after any truncation. The size of
+    /// the message in the queue is [`ResponseForQueryingMessages::payload_bytes`].
+    pub encoded_bytes: u64,
+    /// Number of bytes of the decompressed payload.
+    pub decoded_bytes: u64,
+    /// Indicates if the decompressed payload is whole, rather than its beginning: it is not when the
+    /// compressed payload was truncated, or decompressed to more than the limit.
+    pub complete: bool,
+}
+
+impl From<RabbitMQMessage> for ResponseForQueryingMessages {
+    fn from(message: RabbitMQMessage) -> Self {
+        ResponseForQueryingMessages {
+            payload: message.payload,
+            payload_encoding: message.payload_encoding,
+            payload_bytes: message.payload_bytes,
+            exchange: message.exchange,
+            routing_key: message.routing_key,
+            redelivered: message.redelivered,
+            message_count: message.message_count,
+            properties: message.properties,
+            content_decoding: None,
+            decompression: None,
+        }
+    }
+}
+
+/// Messages of a queue that matched a filter.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct FilteredMessages {
+    /// The matching messages, in queue order.
+    pub messages: Vec<ResponseForQueryingMessages>,
+    /// Number of messages fetched and examined.
+    pub scanned: usize,
+}
+
+impl FilteredMessages {
+    /// Keeps the fetched messages matching `matcher`; [`FilteredMessages::scanned`] tells how many were
+    /// examined.
+    ///
+    /// If the filter has payload criteria, compressed payloads are decompressed within `budget` to be
+    /// matched, and keep their content (see [`ResponseForQueryingMessages::decompress`]). This is
+    /// CPU-bound: async code should then call it from `block_in_place` or `spawn_blocking`.
+    pub fn filter(
+        messages: Vec<ResponseForQueryingMessages>,
+        matcher: &MessageMatcher,
+        budget: &mut DecodeBudget,
+    ) -> FilteredMessages {
+        let scanned = messages.len();
+        let messages = messages
+            .into_iter()
+            .filter_map(|mut message| {
+                if matcher.reads_payload() {
+                    message.decompress(budget);
+                }
+                matcher.matches(&message).then_some(message)
             })
-            .collect()),
-        Err(e) => Err(ServerError::new(format!("{:?}", e))),
+            .collect();
+        FilteredMessages { messages, scanned }
+    }
+}
+
+impl ResponseForQueryingMessages {
+    /// Payload, encoded as told by [`ResponseForQueryingMessages::payload_encoding`].
+    pub fn payload(&self) -> &str {
+        &self.payload
+    }
+
+    /// Encoding of the payload: `string` or `base64`.
+    pub fn payload_encoding(&self) -> &str {
+        &self.payload_encoding
+    }
+
+    /// Number of bytes in the payload, before any truncation.
+    pub fn payload_bytes(&self) -> u64 {
+        self.payload_bytes
+    }
+
+    /// The payload as bytes, decoded if it is base64 encoded, or `None` if it is not valid base64.
+    pub fn decoded_payload(&self) -> Option<Vec<u8>> {
+        match self.payload_encoding.as_str() {
+            "base64" => BASE64_STANDARD.decode(&self.payload).ok(),
+            _ => Some(self.payload.as_bytes().to_vec()),
+        }
+    }
+
+    /// The content of the payload, as a consumer would see it: the payload as bytes, or its
+    /// decompressed content once [`ResponseForQueryingMessages::decompress`] decompressed it. `None` if
+    /// the payload is not valid base64.
+    pub fn content(&self) -> Option<Cow<'_, [u8]>> {
+        if let Some(Decompression::Done { content, .. }) = &self.decompression {
+            return Some(Cow::Borrowed(&content.bytes));
+        }
+        match self.payload_encoding.as_str() {
+            "base64" => BASE64_STANDARD.decode(&self.payload).ok().map(Cow::Owned),
+            _ => Some(Cow::Borrowed(self.payload.as_bytes())),
+        }
+    }
+
+    /// How the payload was decompressed, if it was.
+    pub fn content_decoding(&self) -> Option<&ContentDecoding> {
+        self.content_decoding.as_ref()
+    }
+
+    /// Decompresses the payload according to its `content_encoding`, within `budget`, and keeps the
+    /// content for [`ResponseForQueryingMessages::content`] and
+    /// [`ResponseForQueryingMessages::decode_content`], so that a payload is decompressed once.
+    ///
+    /// Nothing is done if the payload is not compressed with a supported compression, was already
+    /// decompressed, or if the budget is spent. Decompressing is CPU-bound: async code should call this
+    /// from `block_in_place` or `spawn_blocking`.
+    pub fn decompress(&mut self, budget: &mut DecodeBudget) {
+        if self.decompression.is_some() || budget.limit() == 0 {
+            return;
+        }
+        let Some(compression) = self.compression() else {
+            return;
+        };
+        let Some(payload) = self.decoded_payload() else {
+            return;
+        };
+        let decompressed =
+            compression.decompress(&payload, self.is_truncated(&payload), budget.limit());
+        self.decompression = Some(match decompressed {
+            Ok(content) => {
+                budget.spend(content.bytes.len() as u64);
+                Decompression::Done {
+                    content,
+                    encoded_bytes: payload.len() as u64,
+                }
+            }
+            Err(e) => {
+                log::debug!(
+                    "cannot decompress a {:?} payload of {} bytes: {}",
+                    compression,
+                    payload.len(),
+                    e
+                );
+                Decompression::Failed
+            }
+        });
+    }
+
+    /// Replaces a compressed payload with its decompressed content, before it is returned as text or
+    /// base64 like any other payload (see [`PayloadEncoding`]); the original encoding and size are
+    /// kept in [`ResponseForQueryingMessages::content_decoding`].
+    ///
+    /// The payload is decompressed with [`ResponseForQueryingMessages::decompress`], unless it already
+    /// was, and its content is cut to `truncate` bytes if set. Messages whose `content_encoding` is not a
+    /// supported compression, or whose payload cannot be decompressed within `budget`, are returned as
+    /// they are.
+    pub fn decode_content(
+        mut self,
+        encoding: PayloadEncoding,
+        truncate: Option<u64>,
+        budget: &mut DecodeBudget,
+    ) -> Self {
+        self.decompress(budget);
+        let Some(Decompression::Done {
+            mut content,
+            encoded_bytes,
+        }) = self.decompression.take()
+        else {
+            return self;
+        };
+        if let Some(limit) = truncate.filter(|limit| content.bytes.len() as u64 > *limit) {
+            content.bytes.truncate(limit as usize);
+            content.complete = false;
+        }
+        let content_encoding = self.properties.content_encoding.clone().unwrap_or_default();
+        let decoded_bytes = content.bytes.len() as u64;
+        let (payload, payload_encoding) = match (encoding, String::from_utf8(content.bytes)) {
+            (PayloadEncoding::Auto, Ok(text)) => (text, "string"),
+            (_, Ok(text)) => (BASE64_STANDARD.encode(text), "base64"),
+            (_, Err(e)) => (BASE64_STANDARD.encode(e.into_bytes()), "base64"),
+        };
+        self.content_decoding = Some(ContentDecoding {
+            content_encoding,
+            encoded_payload_encoding: std::mem::replace(
+                &mut self.payload_encoding,
+                payload_encoding.to_string(),
+            ),
+            encoded_bytes,
+            decoded_bytes,
+            complete: content.complete,
+        });
+        self.payload = payload;
+        self
+    }
+
+    /// The compression of the payload, if its `content_encoding` is a supported one and it was not
+    /// decompressed yet.
+    fn compression(&self) -> Option<ContentEncoding> {
+        match self.content_decoding {
+            Some(_) => None,
+            None => self
+                .properties
+                .content_encoding()
+                .and_then(ContentEncoding::parse),
+        }
+    }
+
+    /// Indicates if the fetched payload is shorter than the message, i.e. it was truncated.
+    fn is_truncated(&self, payload: &[u8]) -> bool {
+        (payload.len() as u64) < self.payload_bytes
+    }
+
+    /// Exchange the message was published to.
+    pub fn exchange(&self) -> &str {
+        &self.exchange
+    }
+
+    /// Routing key the message was published with.
+    pub fn routing_key(&self) -> &str {
+        &self.routing_key
+    }
+
+    /// Indicates if the message was redelivered.
+    pub fn redelivered(&self) -> bool {
+        self.redelivered
+    }
+
+    /// Number of messages remaining in the queue after this one was fetched.
+    pub fn message_count(&self) -> u64 {
+        self.message_count
+    }
+
+    /// Properties of the message, including its headers.
+    pub fn properties(&self) -> &Properties {
+        &self.properties
+    }
+
+    /// Hash identifying the message by its content, routing information and properties.
+    ///
+    /// The delivery state (`redelivered`, `message_count` and the [`DELIVERY_HEADERS`] the broker
+    /// updates on each delivery) is left out, so that a message has the same fingerprint every time
+    /// it is fetched.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.payload.hash(&mut hasher);
+        self.payload_encoding.hash(&mut hasher);
+        self.exchange.hash(&mut hasher);
+        self.routing_key.hash(&mut hasher);
+        // Serialized through a `Value` so the headers are hashed in a stable (sorted) order.
+        let mut properties = serde_json::to_value(&self.properties).unwrap_or_default();
+        if let Some(headers) = properties
+            .get_mut("headers")
+            .and_then(serde_json::Value::as_object_mut)
+        {
+            headers.retain(|name, _| !DELIVERY_HEADERS.contains(&name.as_str()));
+        }
+        properties.to_string().hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl ManagementClient {
+    /// Fetches the details of a specific queue for a given virtual host.
+    ///
+    /// This method sends an HTTP GET request to the RabbitMQ management API to retrieve the details
+    /// of queues in specified virtual host, using the client's pooled HTTP connection, and deserializes
+    /// the response into a vector of `Queue` structs.
+    ///
+    /// # Arguments
+    ///
+    /// - `vhost`: A string slice that holds the name of the virtual host.
+    ///
+    /// # Returns
+    ///
+    /// - `Result<Vec<Queue>, ServerError>`: On success, returns a vector of `Queue` structs. On failure, returns a `ServerError`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(doctest)] {
+    /// use rabbitmq_messages_management::client::ManagementClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ///     let client = ManagementClient::new("http://localhost:15672", "guest", "guest")?;
+    ///     let queues = client.get_queue_for_vhost("my_vhost").await?;
+    ///     println!("{:?}", queues);
+    ///     Ok(())
+    /// }
+    /// # }
+    /// ```
+    pub async fn get_queue_for_vhost(&self, vhost: &str) -> Result<Vec<Queue>, ServerError> {
+        self.get(&ApiPath::queues(vhost)).await
+    }
+
+    /// Fetches the details of a single queue.
+    pub async fn get_queue(&self, vhost: &str, queue_name: &str) -> Result<Queue, ServerError> {
+        self.get(&ApiPath::queue(vhost, queue_name)).await
+    }
+
+    /// Removes every ready message from a queue. Messages delivered to consumers but not yet
+    /// acknowledged are left alone.
+    pub async fn purge_queue(&self, vhost: &str, queue_name: &str) -> Result<(), ServerError> {
+        self.delete(&ApiPath::queue_contents(vhost, queue_name))
+            .await
+    }
+
+    /// Retrieves messages from a specified queue in a given virtual host.
+    ///
+    /// This method sends an HTTP POST request to the RabbitMQ management API to retrieve messages
+    /// from a specified queue in a given virtual host. The request includes the virtual host, queue name,
+    /// and the number of messages to retrieve. The response is deserialized into a vector of `RabbitMQMessage` structs,
+    /// which are returned, with their payload, routing information and properties, as a vector of
+    /// `ResponseForQueryingMessages` structs.
+    ///
+    /// # Arguments
+    ///
+    /// * `vhost` - A string representing the virtual host from which to retrieve messages.
+    /// * `queue_name` - A string representing the name of the queue from which to retrieve messages.
+    /// * `options` - Number of messages to retrieve, ack mode, payload encoding and truncation limit.
+    ///   Destructive ack modes (see `AckMode::is_destructive`) remove the messages from the queue.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ResponseForQueryingMessages>, ServerError>` - On success, returns a vector of `ResponseForQueryingMessages` structs. On failure, returns a `ServerError`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(doctest)] {
+    /// use rabbitmq_messages_management::client::ManagementClient;
+    /// use rabbitmq_messages_management::rabbitmq::queues::MessageRetrievalOptions;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = ManagementClient::new("http://localhost:15672", "guest", "guest").unwrap();
+    ///     let vhost = "my_vhost".to_string();
+    ///     let queue_name = "my_queue".to_string();
+    ///     let options = MessageRetrievalOptions {
+    ///         count: 10,
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     match client.get_messages_from_a_queue(vhost, queue_name, &options).await {
+    ///         Ok(messages) => {
+    ///             for message in messages {
+    ///                 println!("{:?}", message);
+    ///             }
+    ///         }
+    ///         Err(e) => println!("Failed to retrieve messages: {}", e),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn get_messages_from_a_queue(
+        &self,
+        vhost: String,
+        queue_name: String,
+        options: &MessageRetrievalOptions,
+    ) -> Result<Vec<ResponseForQueryingMessages>, ServerError> {
+        let path = ApiPath::queue_get(&vhost, &queue_name);
+        let request = MessageRetrievalRequest {
+            vhost,
+            name: queue_name,
+            ackmode: options.ackmode,
+            encoding: options.encoding,
+            count: options.count,
+            truncate: options.truncate,
+        };
+        // The following other types implement trait `From<T>`:
+        //  ```
+        //  <isahc::body::AsyncBody as From<&[u8]>>
+        //  <isahc::body::AsyncBody as From<&str>>
+        //  <isahc::body::AsyncBody as From<()>>
+        //  <isahc::body::AsyncBody as From<Vec<u8>>>
+        //  <isahc::body::AsyncBody as From<std::option::Option<T>>>
+        //  <isahc::body::AsyncBody as From<std::string::String>>
+        // ```
+        //
+        // Therefore, if the request body is not of type `&[u8]`, `&str`, `()`, `Vec<u8>`, or `std::string::String`
+        //  make sure you implement it for the request body you're setting.
+        // I suggest use serde_json::to_string to convert the struct to a string and use it as a body.
+
+        let body = serde_json::to_string(&request).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+        let messages: Vec<RabbitMQMessage> = self.post(&path, body).await?;
+
+        Ok(messages
+            .into_iter()
+            .map(ResponseForQueryingMessages::from)
+            .collect())
     }
 }
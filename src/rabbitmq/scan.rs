@@ -0,0 +1,452 @@
+use std::{collections::HashSet, mem};
+
+use serde::Serialize;
+
+use crate::{client::ManagementClient, exceptions::ServerError};
+
+use super::{
+    content::{DecodeBudget, MAX_DECODED_BYTES_PER_REQUEST},
+    filters::MessageMatcher,
+    queues::{
+        AckMode, MessageRetrievalOptions, PayloadEncoding, Queue, QueueType,
+        ResponseForQueryingMessages,
+    },
+};
+
+/// Maximum number of messages a scan fetches in one batch, however deep into the queue it goes.
+pub const MAX_SCAN_BATCH_SIZE: u64 = 1000;
+
+/// Maximum number of messages a scan examines.
+pub const MAX_SCAN_LIMIT: u64 = 100_000;
+
+/// Maximum number of matching messages a scan returns.
+pub const MAX_SCAN_MATCHES: u64 = 1000;
+
+/// Options controlling a scan.
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Number of messages fetched past the ones already examined, at each step.
+    pub batch_size: u64,
+    /// Maximum number of messages to examine.
+    pub limit: u64,
+    /// The scan stops once this many messages matched.
+    pub max_matches: u64,
+    /// How the payload is encoded in the matching messages.
+    pub encoding: PayloadEncoding,
+    /// If set, payloads larger than this many bytes are truncated.
+    pub truncate: Option<u64>,
+    /// Replaces the compressed payloads of the matching messages with their content (see
+    /// [`ResponseForQueryingMessages::decode_content`]).
+    pub decode: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        ScanOptions {
+            batch_size: 100,
+            limit: 10_000,
+            max_matches: 100,
+            encoding: PayloadEncoding::Auto,
+            truncate: None,
+            decode: false,
+        }
+    }
+}
+
+/// Why a scan stopped.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum ScanStop {
+    /// The queue had no ready message.
+    QueueEmpty,
+    /// Every message of the queue was examined: a batch held all the messages left, or batches only
+    /// returned messages examined before once as many messages as the queue held were.
+    Completed,
+    /// Batches only returned messages examined before, and the scan could not fetch further.
+    WrappedAround,
+    /// Batches kept returning the messages at the head of the queue, which puts requeued messages
+    /// back in place, and reaching past them would take a batch of more than
+    /// [`MAX_SCAN_BATCH_SIZE`] messages.
+    HeadRequeued,
+    /// [`ScanOptions::limit`] messages were examined.
+    LimitReached,
+    /// [`ScanOptions::max_matches`] messages matched.
+    MatchLimitReached,
+}
+
+/// How far a scan went.
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct ScanProgress {
+    /// Number of ready messages in the queue when the scan started.
+    pub queue_messages: u64,
+    /// Distinct messages examined.
+    pub scanned: u64,
+    /// Messages matching the filter.
+    pub matched: u64,
+    /// Batches fetched.
+    pub batches: u64,
+    /// Messages fetched (and requeued) over all batches, each one counting as a redelivery.
+    pub deliveries: u64,
+    /// Why the scan stopped, once it did.
+    pub stopped: Option<ScanStop>,
+}
+
+impl ScanProgress {
+    /// Checks that the scan covered what it was asked to: the whole queue, or its limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::Conflict` if the scan has not stopped yet, or stopped without reaching
+    /// every message of the queue ([`ScanStop::WrappedAround`] or [`ScanStop::HeadRequeued`]).
+    pub fn ensure_complete(&self) -> Result<(), ServerError> {
+        let reason = match self.stopped {
+            Some(
+                ScanStop::Completed
+                | ScanStop::QueueEmpty
+                | ScanStop::LimitReached
+                | ScanStop::MatchLimitReached,
+            ) => return Ok(()),
+            Some(ScanStop::HeadRequeued) => format!(
+                "the queue puts fetched messages back at its head, so only its first {} messages \
+                 can be reached",
+                MAX_SCAN_BATCH_SIZE
+            ),
+            Some(ScanStop::WrappedAround) => "batches only returned messages examined before: the \
+                                              others are identical to them, or were consumed"
+                .to_string(),
+            None => "the scan has not finished".to_string(),
+        };
+        Err(ServerError::Conflict {
+            reason: format!(
+                "only {} of the {} messages of the queue were examined: {}",
+                self.scanned, self.queue_messages, reason
+            ),
+        })
+    }
+}
+
+/// Bookkeeping of a scan, independent of the broker: which batch to fetch next and what the fetched
+/// messages bring.
+///
+/// The management API only fetches messages from the head of a queue, and where requeued messages go
+/// depends on the queue type: classic queues put them back at the head, while other queues may put them
+/// at the back. Messages are therefore told apart by their [fingerprint]: a batch repeating messages
+/// already examined means the head was requeued in place, so the next batch is made larger to reach past
+/// them, up to [`MAX_SCAN_BATCH_SIZE`] messages; a batch bringing nothing new means the scan wrapped
+/// around, or could not reach past the head.
+///
+/// [fingerprint]: ResponseForQueryingMessages::fingerprint
+#[derive(Debug)]
+pub struct ScanState {
+    /// Options of the scan.
+    options: ScanOptions,
+    /// Fingerprints of the messages examined so far.
+    seen: HashSet<u64>,
+    /// Number of messages to fetch in the next batch.
+    window: u64,
+    /// How far the scan went.
+    progress: ScanProgress,
+    /// Bytes left to decompress payloads to, over the whole scan.
+    budget: DecodeBudget,
+    /// Warnings not handed out yet.
+    warnings: Vec<String>,
+}
+
+impl ScanState {
+    /// Starts a scan of a queue holding `queue_messages` ready messages.
+    pub fn new(options: ScanOptions, queue_messages: u64) -> ScanState {
+        let options = ScanOptions {
+            batch_size: options.batch_size.clamp(1, MAX_SCAN_BATCH_SIZE),
+            limit: options.limit.max(1),
+            max_matches: options.max_matches.max(1),
+            ..options
+        };
+        ScanState {
+            window: options.batch_size.min(options.limit),
+            options,
+            seen: HashSet::new(),
+            progress: ScanProgress {
+                queue_messages,
+                ..Default::default()
+            },
+            budget: DecodeBudget::default(),
+            warnings: vec![],
+        }
+    }
+
+    /// Number of messages to fetch in the next batch, or `None` once the scan stopped.
+    pub fn next_count(&self) -> Option<u64> {
+        match self.progress.stopped {
+            Some(_) => None,
+            None => Some(self.window),
+        }
+    }
+
+    /// Examines a batch fetched with [`ScanState::next_count`] messages, and returns the messages
+    /// matching `matcher` that were not examined before.
+    ///
+    /// Compressed payloads are decompressed, once, if the filter has payload criteria or the matches
+    /// are decoded, up to [`MAX_DECODED_BYTES_PER_REQUEST`] bytes over the whole scan. This is
+    /// CPU-bound: async code should call it from `block_in_place` or `spawn_blocking`.
+    pub fn absorb(
+        &mut self,
+        fetched: Vec<ResponseForQueryingMessages>,
+        matcher: &MessageMatcher,
+    ) -> Vec<ResponseForQueryingMessages> {
+        let requested = self.window;
+        self.progress.batches += 1;
+        self.progress.deliveries += fetched.len() as u64;
+        if fetched.is_empty() {
+            self.stop(ScanStop::QueueEmpty);
+            return vec![];
+        }
+        let complete = (fetched.len() as u64) < requested;
+
+        let mut matches = vec![];
+        let mut new = 0;
+        let mut repeated = false;
+        let had_budget = self.budget.remaining() > 0;
+        for mut message in fetched {
+            if !self.seen.insert(message.fingerprint()) {
+                repeated = true;
+                continue;
+            }
+            new += 1;
+            self.progress.scanned += 1;
+            if matcher.reads_payload() {
+                message.decompress(&mut self.budget);
+            }
+            if matcher.matches(&message) {
+                self.progress.matched += 1;
+                if self.options.decode {
+                    message = message.decode_content(
+                        self.options.encoding,
+                        self.options.truncate,
+                        &mut self.budget,
+                    );
+                }
+                matches.push(message);
+                if self.progress.matched >= self.options.max_matches {
+                    self.stop(ScanStop::MatchLimitReached);
+                    return matches;
+                }
+            }
+            if self.progress.scanned >= self.options.limit {
+                self.stop(ScanStop::LimitReached);
+                return matches;
+            }
+        }
+
+        if had_budget && self.budget.remaining() == 0 {
+            self.warnings.push(format!(
+                "payloads were decompressed to {} bytes, the most a scan may decompress: the next \
+                 compressed payloads are left compressed, and payload filters do not see their content",
+                MAX_DECODED_BYTES_PER_REQUEST
+            ));
+        }
+
+        let max_window = self.options.limit.min(MAX_SCAN_BATCH_SIZE);
+        if complete || (new == 0 && self.progress.scanned >= self.progress.queue_messages) {
+            self.stop(ScanStop::Completed);
+        } else if new == 0 && self.window >= max_window {
+            // Every message of the largest batch was examined before: with a back-requeueing queue,
+            // the scan went all the way round; otherwise it is stuck behind the head.
+            if self.progress.scanned > self.window || max_window == self.options.limit {
+                self.stop(ScanStop::WrappedAround);
+            } else {
+                self.stop(ScanStop::HeadRequeued);
+            }
+        } else if repeated {
+            self.window = (self.progress.scanned + self.options.batch_size).min(max_window);
+        }
+        matches
+    }
+
+    /// How far the scan went.
+    pub fn progress(&self) -> &ScanProgress {
+        &self.progress
+    }
+
+    /// Hands out the warnings raised since the last call.
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        mem::take(&mut self.warnings)
+    }
+
+    /// Stops the scan, warning if some messages of the queue were not reached.
+    fn stop(&mut self, reason: ScanStop) {
+        self.progress.stopped = Some(reason);
+        let progress = &self.progress;
+        if reason == ScanStop::HeadRequeued {
+            self.warnings.push(format!(
+                "the queue puts fetched messages back at its head, so only its first {} messages \
+                 can be scanned; {} messages were not reached",
+                MAX_SCAN_BATCH_SIZE,
+                progress.queue_messages.saturating_sub(progress.scanned)
+            ));
+        } else if matches!(reason, ScanStop::Completed | ScanStop::WrappedAround)
+            && progress.scanned < progress.queue_messages
+        {
+            self.warnings.push(format!(
+                "only {} of the {} messages in the queue were reached; the others were consumed, \
+                 expired or are identical to messages already examined",
+                progress.scanned, progress.queue_messages
+            ));
+        }
+    }
+}
+
+/// Side effects of scanning a queue that its users should know about.
+pub fn scan_warnings(queue: &Queue) -> Vec<String> {
+    let mut warnings = vec![
+        "scanning fetches messages and requeues them: every fetched message is flagged as \
+         redelivered, messages near the head are fetched once per batch, and consumers may receive \
+         messages out of order while the scan runs"
+            .to_string(),
+    ];
+    if let Some(limit) = queue.delivery_limit() {
+        warnings.push(format!(
+            "the queue has a delivery limit of {}: each fetch counts as a delivery, so messages \
+             fetched repeatedly may be dead-lettered or dropped",
+            limit
+        ));
+    }
+    if queue.consumers() > 0 {
+        warnings.push(format!(
+            "the queue has {} consumers: messages delivered to them are not scanned",
+            queue.consumers()
+        ));
+    }
+    warnings
+}
+
+/// Checks that a scan of up to `limit` messages can reach them all.
+///
+/// # Errors
+///
+/// Returns `ServerError::Conflict` for a classic queue holding more than [`MAX_SCAN_BATCH_SIZE`] ready
+/// messages when more than that are asked for: it puts fetched messages back at its head, so a scan
+/// would stop with [`ScanStop::HeadRequeued`].
+pub fn ensure_reachable(queue: &Queue, limit: u64) -> Result<(), ServerError> {
+    let wanted = limit.min(queue.messages_ready());
+    if matches!(queue.queue_type(), QueueType::Classic(_)) && wanted > MAX_SCAN_BATCH_SIZE {
+        return Err(ServerError::Conflict {
+            reason: format!(
+                "the queue holds {} ready messages, but as a classic queue puts fetched messages \
+                 back at its head, only its first {} can be reached",
+                queue.messages_ready(),
+                MAX_SCAN_BATCH_SIZE
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// A scan in progress through the messages of a queue, started by [`ManagementClient::scan_queue`].
+#[derive(Debug)]
+pub struct QueueScan {
+    /// Client the batches are fetched with.
+    client: ManagementClient,
+    /// Vhost of the queue.
+    vhost: String,
+    /// Name of the queue.
+    queue_name: String,
+    /// The queue, as it was when the scan started.
+    queue: Queue,
+    /// Filter the messages must match.
+    matcher: MessageMatcher,
+    /// Bookkeeping of the scan.
+    state: ScanState,
+}
+
+impl QueueScan {
+    /// Fetches the next batch, requeueing its messages, or returns `None` once the scan stopped. The
+    /// batch is to be examined with [`QueueScan::absorb`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the management API if the batch cannot be fetched. The scan can be
+    /// resumed by calling this method again.
+    pub async fn next_batch(
+        &mut self,
+    ) -> Result<Option<Vec<ResponseForQueryingMessages>>, ServerError> {
+        let Some(count) = self.state.next_count() else {
+            return Ok(None);
+        };
+        let options = MessageRetrievalOptions {
+            count,
+            ackmode: AckMode::AckRequeueTrue,
+            encoding: self.state.options.encoding,
+            truncate: self.state.options.truncate,
+        };
+        let fetched = self
+            .client
+            .get_messages_from_a_queue(self.vhost.clone(), self.queue_name.clone(), &options)
+            .await?;
+        Ok(Some(fetched))
+    }
+
+    /// Examines a batch returned by [`QueueScan::next_batch`], and returns the new matching messages.
+    ///
+    /// This decompresses payloads if the filter or the options need their content (see
+    /// [`ScanState::absorb`]), which is CPU-bound: async code should then call it from
+    /// `block_in_place` or `spawn_blocking`.
+    pub fn absorb(
+        &mut self,
+        fetched: Vec<ResponseForQueryingMessages>,
+    ) -> Vec<ResponseForQueryingMessages> {
+        self.state.absorb(fetched, &self.matcher)
+    }
+
+    /// How far the scan went.
+    pub fn progress(&self) -> &ScanProgress {
+        self.state.progress()
+    }
+
+    /// The queue, as it was when the scan started.
+    pub fn queue(&self) -> &Queue {
+        &self.queue
+    }
+
+    /// Hands out the warnings raised since the last call, starting with [`scan_warnings`].
+    pub fn take_warnings(&mut self) -> Vec<String> {
+        self.state.take_warnings()
+    }
+}
+
+impl ManagementClient {
+    /// Starts scanning a queue for the messages matching `matcher`, past the first batch the
+    /// management API returns.
+    ///
+    /// The scan only requeues messages, so it never removes any, but it has side effects: see
+    /// [`scan_warnings`]. Identical messages (same payload, routing and properties) are examined once.
+    ///
+    /// # Errors
+    ///
+    /// Returns `ServerError::InvalidRequest` for stream queues, whose messages cannot be fetched through
+    /// the management API, and the error of the management API if the queue cannot be read.
+    pub async fn scan_queue(
+        &self,
+        vhost: &str,
+        queue_name: &str,
+        matcher: MessageMatcher,
+        options: ScanOptions,
+    ) -> Result<QueueScan, ServerError> {
+        let queue = self.get_queue(vhost, queue_name).await?;
+        if matches!(queue.queue_type(), QueueType::Stream(_)) {
+            return Err(ServerError::InvalidRequest {
+                reason: "stream queues cannot be read through the management API".to_string(),
+            });
+        }
+        let mut state = ScanState::new(options, queue.messages_ready());
+        state.warnings = scan_warnings(&queue);
+        Ok(QueueScan {
+            client: self.clone(),
+            vhost: vhost.to_string(),
+            queue_name: queue_name.to_string(),
+            queue,
+            matcher,
+            state,
+        })
+    }
+}
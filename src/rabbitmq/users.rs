@@ -0,0 +1,75 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{client::ManagementClient, exceptions::ServerError};
+
+use super::path::ApiPath;
+
+/// Represents the user the management API authenticated a request as.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct WhoAmI {
+    /// Name of the user.
+    name: String,
+    /// Tags of the user, e.g. `administrator` or `monitoring`.
+    #[serde(default, deserialize_with = "tags")]
+    tags: Vec<String>,
+}
+
+impl WhoAmI {
+    /// Name of the user.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Tags of the user.
+    pub fn tags(&self) -> &[String] {
+        &self.tags
+    }
+}
+
+/// Deserializes user tags, which older brokers return as a comma separated string and newer ones as a list.
+fn tags<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(crate = "rocket::serde", untagged)]
+    enum Tags {
+        List(Vec<String>),
+        Text(String),
+    }
+
+    Ok(match Tags::deserialize(deserializer)? {
+        Tags::List(tags) => tags,
+        Tags::Text(text) => text
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+impl ManagementClient {
+    /// Retrieves the user the management API authenticates this client as.
+    ///
+    /// Useful to check credentials: a `ServerError::UpstreamStatus` with status 401 means they were rejected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(doctest)] {
+    /// use rabbitmq_messages_management::client::ManagementClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = ManagementClient::new("http://localhost:15672", "guest", "guest").unwrap();
+    ///     let user = client.whoami().await.unwrap();
+    ///     println!("{} {:?}", user.name(), user.tags());
+    /// }
+    /// # }
+    /// ```
+    pub async fn whoami(&self) -> Result<WhoAmI, ServerError> {
+        self.get(&ApiPath::whoami()).await
+    }
+}
@@ -1,9 +1,8 @@
 use std::collections::HashMap;
 
-use rabbitmq_messages_management::exceptions::ServerError;
-use rabbitmq_messages_management::{
-    constants::RABBITMQ_MANAGEMENT_ROOT, prepare_authorization_headers, prepare_url, send_get,
-};
+use crate::{client::ManagementClient, exceptions::ServerError};
+
+use super::path::ApiPath;
 use serde::{Deserialize, Serialize};
 
 /// Represents metadata for a RabbitMQ virtual host.
@@ -37,49 +36,57 @@ pub(crate) struct RabbitMQVhost {
 /// Represents vhost names internally
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(crate = "rocket::serde")]
-pub(crate) struct ResponseForQueryingVhosts {
+pub struct ResponseForQueryingVhosts {
     /// Name of vhost
     name: String,
 }
 
-/// Retrieves the list of virtual hosts from the RabbitMQ management API.
-///
-/// This function sends an HTTP GET request to the RabbitMQ management API to retrieve the list of virtual hosts.
-/// The response is deserialized into a vector of `RabbitMQVhost` structs, and the names of these virtual hosts
-/// are returned as a vector of `ResponseForQueryingVhosts` structs.
-///
-/// # Returns
-///
-/// * `Result<Vec<ResponseForQueryingVhosts>, ()>` - On success, returns a vector of `ResponseForQueryingVhosts` structs. On failure, returns an empty tuple `()`.
-///
-/// # Example
-///
-/// ```rust
-/// #[tokio::main]
-/// async fn main() {
-///     match get_vhosts().await {
-///         Ok(vhosts) => {
-///             for vhost in vhosts {
-///                 println!("Virtual Host Name: {}", vhost.name);
-///             }
-///         }
-///         Err(_) => println!("Failed to retrieve virtual hosts"),
-///     }
-/// }
-/// ```
-pub async fn get_vhosts() -> Result<Vec<ResponseForQueryingVhosts>, ServerError> {
-    let root = &dotenv::var(RABBITMQ_MANAGEMENT_ROOT).expect("RABBITMQ_MANAGEMENT_ROOT not set");
-    let url = prepare_url(&root, "api/vhosts").unwrap();
-    let vhost_responses: Result<Vec<RabbitMQVhost>, ()> =
-        send_get(&url, Some(&prepare_authorization_headers())).await;
+impl ResponseForQueryingVhosts {
+    /// Name of the vhost.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl ManagementClient {
+    /// Retrieves the list of virtual hosts from the RabbitMQ management API.
+    ///
+    /// This method sends an HTTP GET request to the RabbitMQ management API to retrieve the list of virtual hosts.
+    /// The response is deserialized into a vector of `RabbitMQVhost` structs, and the names of these virtual hosts
+    /// are returned as a vector of `ResponseForQueryingVhosts` structs.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ResponseForQueryingVhosts>, ServerError>` - On success, returns a vector of `ResponseForQueryingVhosts` structs. On failure, returns a `ServerError`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # #[cfg(doctest)] {
+    /// use rabbitmq_messages_management::client::ManagementClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let client = ManagementClient::new("http://localhost:15672", "guest", "guest").unwrap();
+    ///     match client.get_vhosts().await {
+    ///         Ok(vhosts) => {
+    ///             for vhost in vhosts {
+    ///                 println!("Virtual Host: {:?}", vhost);
+    ///             }
+    ///         }
+    ///         Err(e) => println!("Failed to retrieve virtual hosts: {}", e),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub async fn get_vhosts(&self) -> Result<Vec<ResponseForQueryingVhosts>, ServerError> {
+        let vhosts: Vec<RabbitMQVhost> = self.get(&ApiPath::vhosts()).await?;
 
-    match vhost_responses {
-        Ok(vhosts) => Ok(vhosts
+        Ok(vhosts
             .iter()
             .map(|vhost| ResponseForQueryingVhosts {
                 name: vhost.name.clone(),
             })
-            .collect()),
-        Err(e) => Err(ServerError::new(format!("{:?}", e))),
+            .collect())
     }
 }
@@ -0,0 +1,192 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+use rabbitmq_messages_management::{
+    audit::{AuditLog, AuditOutcome, AuditQuery, AuditRecord},
+    auth::Principal,
+    exceptions::{FieldError, ServerError},
+};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    form,
+    request::{FromRequest, Outcome},
+    serde::{json::Json, Serialize},
+    tokio::task,
+    Request, Response, State,
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+use super::{
+    auth::{Admin, Authorized},
+    guard_error, route_param,
+};
+
+/// Maximum number of records returned by one query.
+const MAX_AUDIT_RECORDS: isize = 1000;
+
+/// Parameters of an operation given in the request body, recorded in the audit log along with the
+/// query parameters.
+#[derive(Default)]
+struct BodyParameters(Mutex<BTreeMap<String, String>>);
+
+/// Request guard through which routes record the parameters their request body carries, such as
+/// the destination of a move, in the audit log.
+pub struct AuditParameters<'r>(&'r BodyParameters);
+
+impl AuditParameters<'_> {
+    /// Records a parameter: strings as they are, other values as JSON.
+    pub fn record(&self, name: &str, value: impl Serialize) {
+        let value = match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(text)) => text,
+            Ok(value) => value.to_string(),
+            Err(e) => format!("<{}>", e),
+        };
+        let mut parameters = self.0 .0.lock().unwrap_or_else(|e| e.into_inner());
+        parameters.insert(name.to_string(), value);
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuditParameters<'r> {
+    type Error = ServerError;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(AuditParameters(
+            request.local_cache(BodyParameters::default),
+        ))
+    }
+}
+
+/// Records every API route invocation in the [`AuditLog`], if auditing is enabled.
+///
+/// Requests served by the static file server, and requests that did not match a route, are not
+/// recorded. The parameters of a record are the query parameters, plus the body parameters routes
+/// recorded through [`AuditParameters`].
+pub struct Auditor;
+
+#[rocket::async_trait]
+impl Fairing for Auditor {
+    fn info(&self) -> Info {
+        Info {
+            name: "Audit log",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        let Some(Some(log)) = request.rocket().state::<Option<AuditLog>>() else {
+            return;
+        };
+        let Some(route) = request.route() else {
+            return;
+        };
+        if route.uri.base() == "/" {
+            return;
+        }
+
+        let status = response.status();
+        let mut parameters = request
+            .query_fields()
+            .map(|field| (field.name.to_string(), field.value.to_string()))
+            .collect::<BTreeMap<_, _>>();
+        let body = request.local_cache(BodyParameters::default);
+        parameters.extend(
+            body.0
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone())),
+        );
+        let record = AuditRecord {
+            time: OffsetDateTime::now_utc(),
+            principal: request
+                .local_cache(|| None::<Principal>)
+                .as_ref()
+                .map(|principal| principal.name.clone()),
+            operation: route.name.as_deref().unwrap_or_default().to_string(),
+            request: format!("{} {}", request.method(), request.uri().path()),
+            cluster: (route.uri.base() == "/clusters")
+                .then(|| request.param::<&str>(0).and_then(Result::ok))
+                .flatten()
+                .map(str::to_string),
+            vhost: route_param(request, "vhost").map(str::to_string),
+            queue: route_param(request, "queue_name").map(str::to_string),
+            parameters,
+            outcome: if status.class().is_success() || status.class().is_redirection() {
+                AuditOutcome::Success
+            } else {
+                AuditOutcome::Failure
+            },
+            status: status.code,
+            error: guard_error(request).as_ref().map(ServerError::to_string),
+        };
+        // Writing blocks, on the file and on the lock other requests may hold.
+        if let Err(e) = task::block_in_place(|| log.append(&record)) {
+            log::error!("{}", e);
+        }
+    }
+}
+
+/// Query parameters accepted when reading the audit log.
+#[derive(FromForm, Debug)]
+pub struct AuditParams {
+    /// Only records at or after this RFC 3339 time.
+    #[field(validate = with(rfc3339, "expected an RFC 3339 time"))]
+    since: Option<String>,
+    /// Only records before this RFC 3339 time.
+    #[field(validate = with(rfc3339, "expected an RFC 3339 time"))]
+    until: Option<String>,
+    /// Only records of this principal.
+    principal: Option<String>,
+    /// Only records targeting this cluster.
+    cluster: Option<String>,
+    /// Only records targeting this vhost.
+    vhost: Option<String>,
+    /// Only records targeting this queue.
+    queue: Option<String>,
+    /// Maximum number of records returned, the most recent ones being kept.
+    #[field(default = 100, validate = range(1..=MAX_AUDIT_RECORDS))]
+    limit: usize,
+}
+
+/// Indicates if an optional time parameter is absent or valid RFC 3339.
+fn rfc3339(value: &Option<String>) -> bool {
+    value
+        .as_deref()
+        .is_none_or(|value| OffsetDateTime::parse(value, &Rfc3339).is_ok())
+}
+
+impl From<AuditParams> for AuditQuery {
+    fn from(params: AuditParams) -> Self {
+        let parse = |value: Option<String>| {
+            value.and_then(|value| OffsetDateTime::parse(&value, &Rfc3339).ok())
+        };
+        AuditQuery {
+            since: parse(params.since),
+            until: parse(params.until),
+            principal: params.principal,
+            cluster: params.cluster,
+            vhost: params.vhost,
+            queue: params.queue,
+            limit: params.limit,
+        }
+    }
+}
+
+/// Returns the most recent audit records matching the query, oldest first. Admins only.
+#[get("/?<params..>")]
+pub async fn audit(
+    _user: Authorized<Admin>,
+    log: &State<Option<AuditLog>>,
+    params: form::Result<'_, AuditParams>,
+) -> Result<Json<Vec<AuditRecord>>, ServerError> {
+    let params = params.map_err(|errors| ServerError::InvalidParameters {
+        errors: FieldError::from_form_errors(&errors),
+    })?;
+    let Some(log) = log.inner() else {
+        return Err(ServerError::InvalidRequest {
+            reason: "the audit log is disabled".to_string(),
+        });
+    };
+    let query = AuditQuery::from(params);
+    Ok(Json(task::block_in_place(|| log.query(&query))?))
+}
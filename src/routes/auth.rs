@@ -0,0 +1,285 @@
+use std::marker::PhantomData;
+
+use base64::prelude::*;
+use rabbitmq_messages_management::{
+    auth::{AuthMethod, Authenticator, Authorizer, Principal, Role, ANONYMOUS},
+    clusters::Clusters,
+    config::AppConfig,
+    exceptions::ServerError,
+};
+use rocket::{
+    http::{Cookie, CookieJar, SameSite, Status},
+    outcome::Outcome,
+    request::{self, FromRequest},
+    serde::{json::Json, Deserialize, Serialize},
+    time::{Duration, OffsetDateTime},
+    Request, State,
+};
+
+use super::{fail, route_param};
+
+/// Name of the private cookie holding the session of a logged in user.
+const SESSION_COOKIE: &str = "viewer_session";
+
+/// A session of a user logged in to this server, kept in an encrypted private cookie.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct Session {
+    /// Name of the user.
+    name: String,
+    /// Unix timestamp the session expires at.
+    expires: i64,
+}
+
+/// Credentials a user logs in to this server with.
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct LoginRequest {
+    /// Username from the users file.
+    username: String,
+    /// Password of the user.
+    password: String,
+}
+
+/// The authenticated client of a request.
+///
+/// Clients authenticate with, in order of precedence, an `Authorization: Bearer` API token, HTTP basic
+/// credentials checked against the users file, or the session cookie set by [`login`]. When
+/// authentication is disabled every request is made by the [`ANONYMOUS`] principal.
+pub struct Authenticated(pub Principal);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Authenticated {
+    type Error = ServerError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let Some(authenticator) = request.rocket().state::<Authenticator>() else {
+            return fail(
+                request,
+                ServerError::Configuration {
+                    reason: "authentication is not configured".to_string(),
+                },
+            );
+        };
+        if !authenticator.is_enabled() {
+            let principal = Principal::new(ANONYMOUS, AuthMethod::Anonymous);
+            request.local_cache(|| Some(principal.clone()));
+            return Outcome::Success(Authenticated(principal));
+        }
+
+        let (principal, reason) = match request.headers().get_one("Authorization") {
+            Some(header) => (
+                authenticate_header(authenticator, header).await,
+                "invalid credentials",
+            ),
+            None => (
+                session_principal(request.cookies()),
+                "log in, or send an API token or basic credentials",
+            ),
+        };
+        match principal {
+            Some(principal) => {
+                request.local_cache(|| Some(principal.clone()));
+                Outcome::Success(Authenticated(principal))
+            }
+            None => fail(
+                request,
+                ServerError::Unauthenticated {
+                    reason: reason.to_string(),
+                },
+            ),
+        }
+    }
+}
+
+/// A role a route requires, as a type so it can parameterize [`Authorized`].
+pub trait RequiredRole {
+    /// The minimum role.
+    const ROLE: Role;
+}
+
+/// Requires the [`Role::Viewer`] role.
+pub enum Viewer {}
+
+/// Requires the [`Role::Operator`] role.
+pub enum Operator {}
+
+/// Requires the [`Role::Admin`] role.
+pub enum Admin {}
+
+impl RequiredRole for Viewer {
+    const ROLE: Role = Role::Viewer;
+}
+
+impl RequiredRole for Operator {
+    const ROLE: Role = Role::Operator;
+}
+
+impl RequiredRole for Admin {
+    const ROLE: Role = Role::Admin;
+}
+
+/// An [`Authenticated`] principal holding at least the role `R` on the cluster named by the first
+/// path segment of the route and, if the route has a `<vhost>` segment, on that vhost. Routes outside
+/// of `/clusters` need the role on every vhost of every configured cluster (see
+/// [`Authorizer::global_role`]).
+///
+/// Routes whose required role depends on their parameters check the resolved `role` further with
+/// [`Authorized::require`].
+pub struct Authorized<R: RequiredRole> {
+    /// The authenticated principal.
+    pub principal: Principal,
+    /// Role of the principal on the cluster or vhost.
+    pub role: Role,
+    required: PhantomData<R>,
+}
+
+impl<R: RequiredRole> Authorized<R> {
+    /// Fails with `ServerError::Forbidden` unless the principal holds at least `role`.
+    pub fn require(&self, role: Role, action: &str) -> Result<(), ServerError> {
+        if self.role >= role {
+            Ok(())
+        } else {
+            Err(forbidden(&self.principal, role, action))
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, R: RequiredRole> FromRequest<'r> for Authorized<R> {
+    type Error = ServerError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let Authenticated(principal) = match request.guard::<Authenticated>().await {
+            Outcome::Success(authenticated) => authenticated,
+            Outcome::Error(error) => return Outcome::Error(error),
+            Outcome::Forward(status) => return Outcome::Forward(status),
+        };
+        let (Some(authorizer), Some(clusters)) = (
+            request.rocket().state::<Authorizer>(),
+            request.rocket().state::<Clusters>(),
+        ) else {
+            return fail(
+                request,
+                ServerError::Configuration {
+                    reason: "authorization is not configured".to_string(),
+                },
+            );
+        };
+        let cluster = request
+            .route()
+            .filter(|route| route.uri.base() == "/clusters")
+            .and_then(|_| request.param::<&str>(0))
+            .and_then(Result::ok);
+        let role = match (cluster, route_param(request, "vhost")) {
+            (None, _) => authorizer.global_role(&principal.name, clusters.ids()),
+            (Some(cluster), Some(vhost)) => authorizer.role(&principal.name, cluster, vhost),
+            (Some(cluster), None) => authorizer.cluster_role(&principal.name, cluster),
+        };
+        match role {
+            Some(role) if role >= R::ROLE => Outcome::Success(Authorized {
+                principal,
+                role,
+                required: PhantomData,
+            }),
+            _ => fail(
+                request,
+                forbidden(
+                    &principal,
+                    R::ROLE,
+                    &format!("{} {}", request.method(), request.uri()),
+                ),
+            ),
+        }
+    }
+}
+
+/// Builds the error returned when a principal lacks a role.
+fn forbidden(principal: &Principal, role: Role, action: &str) -> ServerError {
+    ServerError::Forbidden {
+        reason: format!("{} needs the {} role to {}", principal.name, role, action),
+    }
+}
+
+/// Authenticates the value of an `Authorization` header.
+async fn authenticate_header(authenticator: &Authenticator, header: &str) -> Option<Principal> {
+    let (scheme, credentials) = header.split_once(' ')?;
+    if scheme.eq_ignore_ascii_case("bearer") {
+        authenticator.authenticate_token(credentials.trim())
+    } else if scheme.eq_ignore_ascii_case("basic") {
+        let decoded = BASE64_STANDARD.decode(credentials.trim()).ok()?;
+        let decoded = String::from_utf8(decoded).ok()?;
+        let (username, password) = decoded.split_once(':')?;
+        authenticator
+            .authenticate_password(username, password, AuthMethod::Basic)
+            .await
+    } else {
+        None
+    }
+}
+
+/// Reads the principal of an unexpired session cookie.
+fn session_principal(cookies: &CookieJar<'_>) -> Option<Principal> {
+    let cookie = cookies.get_private(SESSION_COOKIE)?;
+    let session: Session = rocket::serde::json::from_str(cookie.value()).ok()?;
+    (session.expires > OffsetDateTime::now_utc().unix_timestamp())
+        .then(|| Principal::new(&session.name, AuthMethod::Session))
+}
+
+/// Logs in to this server with a username and password from the users file.
+///
+/// Sets an encrypted, HTTP-only session cookie valid for `auth.session_ttl_secs`.
+#[post("/session", data = "<credentials>")]
+pub async fn login(
+    authenticator: &State<Authenticator>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    credentials: Json<LoginRequest>,
+) -> Result<Json<Principal>, ServerError> {
+    if !authenticator.is_enabled() {
+        return Err(ServerError::InvalidRequest {
+            reason: "authentication is disabled".to_string(),
+        });
+    }
+    let principal = authenticator
+        .authenticate_password(
+            &credentials.username,
+            &credentials.password,
+            AuthMethod::Session,
+        )
+        .await
+        .ok_or_else(|| ServerError::Unauthenticated {
+            reason: "invalid username or password".to_string(),
+        })?;
+
+    let ttl = Duration::seconds(config.auth.session_ttl_secs as i64);
+    let session = Session {
+        name: principal.name.clone(),
+        expires: (OffsetDateTime::now_utc() + ttl).unix_timestamp(),
+    };
+    let value =
+        rocket::serde::json::to_string(&session).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+    cookies.add_private(
+        Cookie::build((SESSION_COOKIE, value))
+            .path("/")
+            .max_age(ttl)
+            .http_only(true)
+            .same_site(SameSite::Strict),
+    );
+    Ok(Json(principal))
+}
+
+/// Describes the principal the request is authenticated as.
+#[get("/session")]
+pub fn me(user: Authenticated) -> Json<Principal> {
+    Json(user.0)
+}
+
+/// Logs out of this server.
+#[delete("/session")]
+pub fn logout(cookies: &CookieJar<'_>) -> Status {
+    cookies.remove_private(Cookie::build(SESSION_COOKIE).path("/"));
+    Status::NoContent
+}
@@ -0,0 +1,27 @@
+use rabbitmq_messages_management::{
+    auth::Authorizer,
+    clusters::{ClusterSummary, Clusters},
+};
+use rocket::{serde::json::Json, State};
+
+use super::auth::Authenticated;
+
+/// Lists the clusters the principal has a role on.
+#[get("/")]
+pub async fn clusters(
+    user: Authenticated,
+    clusters: &State<Clusters>,
+    authorizer: &State<Authorizer>,
+) -> Json<Vec<ClusterSummary>> {
+    Json(
+        clusters
+            .summaries()
+            .into_iter()
+            .filter(|cluster| {
+                authorizer
+                    .cluster_role(&user.0.name, cluster.id())
+                    .is_some()
+            })
+            .collect(),
+    )
+}
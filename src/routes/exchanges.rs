@@ -0,0 +1,32 @@
+use rabbitmq_messages_management::{
+    exceptions::ServerError,
+    rabbitmq::exchanges::{MessageToPublish, PublishResponse},
+};
+use rocket::serde::json::{self, Json};
+
+use super::{
+    audit::AuditParameters,
+    auth::{Authorized, Operator},
+    session::ClusterClient,
+};
+
+/// Publishes a message to an exchange. `routed` tells whether any queue received it.
+#[post("/<_>/exchanges/<vhost>/<exchange>/publish", data = "<message>")]
+pub async fn publish(
+    _user: Authorized<Operator>,
+    client: ClusterClient,
+    vhost: &str,
+    exchange: &str,
+    message: Result<Json<MessageToPublish>, json::Error<'_>>,
+    audit: AuditParameters<'_>,
+) -> Result<Json<PublishResponse>, ServerError> {
+    let message = message.map_err(|e| ServerError::InvalidRequest {
+        reason: format!("invalid message: {}", e),
+    })?;
+    audit.record("exchange", exchange);
+    audit.record("routing_key", &message.routing_key);
+    if let Some(message_id) = message.properties.message_id() {
+        audit.record("message_id", message_id);
+    }
+    Ok(Json(client.publish(vhost, exchange, &message).await?))
+}
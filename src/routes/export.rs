@@ -0,0 +1,195 @@
+use std::io;
+
+use bytes::Bytes;
+use rabbitmq_messages_management::{
+    exceptions::{FieldError, ServerError},
+    export::{export_file_name, ExportColumn, ExportEncoder, ExportFormat},
+    rabbitmq::{
+        content::DecodeBudget,
+        queues::PayloadEncoding,
+        scan::{ensure_reachable, ScanOptions, MAX_SCAN_LIMIT},
+    },
+};
+use rocket::{
+    form,
+    http::{ContentType, Header},
+    request::Request,
+    response::{self, Responder, Response},
+    tokio::{io::AsyncRead, task},
+};
+use tokio_util::io::StreamReader;
+
+use super::{
+    auth::{Authorized, Operator},
+    filters::QueryFilter,
+    session::ClusterClient,
+};
+
+/// Number of new messages fetched at each step of an export, whatever its `count`, so that each
+/// response of the management API stays small. Batches refetching the head of a classic queue are
+/// bounded by [`MAX_SCAN_BATCH_SIZE`].
+///
+/// [`MAX_SCAN_BATCH_SIZE`]: rabbitmq_messages_management::rabbitmq::scan::MAX_SCAN_BATCH_SIZE
+const EXPORT_BATCH_SIZE: u64 = 200;
+
+/// Query parameters accepted when exporting messages, besides the message filters.
+#[derive(FromForm, Debug)]
+pub struct ExportQuery {
+    /// Format of the export.
+    #[field(default = ExportFormat::Ndjson)]
+    format: ExportFormat,
+    /// Number of messages to examine, from the head of the queue.
+    #[field(validate = range(1..=MAX_SCAN_LIMIT as isize))]
+    count: u64,
+    /// Columns of a CSV export.
+    columns: Vec<String>,
+    /// How the payload is encoded in NDJSON and CSV exports.
+    #[field(default = PayloadEncoding::Auto)]
+    encoding: PayloadEncoding,
+}
+
+/// A file download whose body is written as it is sent.
+pub struct Download<R> {
+    /// Type of the file.
+    content_type: ContentType,
+    /// Name the file is saved as.
+    file_name: String,
+    /// Contents of the file.
+    body: R,
+}
+
+impl<'r, R: AsyncRead + Send + 'r> Responder<'r, 'r> for Download<R> {
+    fn respond_to(self, _request: &'r Request<'_>) -> response::Result<'r> {
+        Response::build()
+            .header(self.content_type)
+            .header(Header::new(
+                "Content-Disposition",
+                format!("attachment; filename=\"{}\"", self.file_name),
+            ))
+            .streamed_body(self.body)
+            .ok()
+    }
+}
+
+/// Exports the messages of a queue matching the filters, as NDJSON, CSV or a `tar.zst` archive.
+///
+/// Messages are fetched in batches like [`super::scan::scan`] does, so the export has the same side
+/// effects and is for operators too, and each batch is encoded and sent before the next one is fetched. Exports that cannot
+/// reach every message they ask for are refused with `409` up front when that is known (see
+/// [`ensure_reachable`]). If fetching a batch fails once the export started, or the scan stops without
+/// reaching every message (see [`ScanProgress::ensure_complete`]), the body ends there, without the
+/// ending of the file.
+///
+/// [`ScanProgress::ensure_complete`]: rabbitmq_messages_management::rabbitmq::scan::ScanProgress::ensure_complete
+#[get("/<cluster>/queues/<vhost>/<queue_name>/export?<query..>")]
+pub async fn export(
+    _user: Authorized<Operator>,
+    client: ClusterClient,
+    cluster: &str,
+    vhost: &str,
+    queue_name: &str,
+    query: form::Result<'_, ExportQuery>,
+    filter: QueryFilter,
+) -> Result<Download<impl AsyncRead + Send>, ServerError> {
+    let query = query.map_err(|errors| ServerError::InvalidParameters {
+        errors: FieldError::from_form_errors(&errors),
+    })?;
+    if query.format != ExportFormat::Csv && !query.columns.is_empty() {
+        return Err(ServerError::InvalidParameters {
+            errors: vec![FieldError {
+                field: "columns".to_string(),
+                message: "columns can only be chosen for CSV exports".to_string(),
+            }],
+        });
+    }
+    let columns = match query.format {
+        ExportFormat::Csv => ExportColumn::parse_all(&query.columns)?,
+        _ => vec![],
+    };
+    // JSONPath columns select from the content of compressed payloads, which are exported as stored.
+    let reads_content = columns
+        .iter()
+        .any(|(_, column)| matches!(column, ExportColumn::JsonPath(_)));
+    let mut encoder = ExportEncoder::new(query.format, columns)?;
+    let options = ScanOptions {
+        batch_size: EXPORT_BATCH_SIZE.min(query.count),
+        limit: query.count,
+        max_matches: query.count,
+        encoding: match query.format {
+            ExportFormat::TarZstd => PayloadEncoding::Base64,
+            _ => query.encoding,
+        },
+        truncate: None,
+        decode: false,
+    };
+    let mut scan = client
+        .scan_queue(vhost, queue_name, filter.matcher, options)
+        .await?;
+    ensure_reachable(scan.queue(), query.count)?;
+    for warning in scan.take_warnings() {
+        log::info!("exporting queue {:?}: {}", queue_name, warning);
+    }
+
+    let queue = queue_name.to_string();
+    let request_id = client.request_id().to_string();
+    let mut budget = DecodeBudget::default();
+    let chunks = async_stream::stream! {
+        loop {
+            let output = match scan.next_batch().await {
+                Ok(Some(fetched)) => {
+                    let messages = task::block_in_place(|| {
+                        let mut messages = scan.absorb(fetched);
+                        if reads_content {
+                            messages
+                                .iter_mut()
+                                .for_each(|message| message.decompress(&mut budget));
+                        }
+                        messages
+                    });
+                    messages
+                        .iter()
+                        .try_for_each(|message| encoder.write(message))
+                        .and_then(|_| encoder.take_output())
+                }
+                Ok(None) => break,
+                Err(error) => Err(error),
+            };
+            match output {
+                Ok(output) => yield Ok(Bytes::from(output)),
+                Err(error) => {
+                    log::warn!(
+                        "request_id={} export of queue {:?} failed after {} messages: {}",
+                        request_id,
+                        queue,
+                        encoder.written(),
+                        error
+                    );
+                    yield Err(io::Error::other(error.public_message()));
+                    return;
+                }
+            }
+        }
+        for warning in scan.take_warnings() {
+            log::warn!("exporting queue {:?}: {}", queue, warning);
+        }
+        // A file missing messages the scan could not reach must not look complete.
+        if let Err(error) = scan.progress().ensure_complete() {
+            log::warn!(
+                "export of queue {:?} stopped after {} messages: {}",
+                queue,
+                encoder.written(),
+                error
+            );
+            yield Err(io::Error::other(error.to_string()));
+            return;
+        }
+        log::info!("exported {} messages of queue {:?}", encoder.written(), queue);
+        yield encoder.finish().map(Bytes::from).map_err(|e| io::Error::other(e.to_string()));
+    };
+    Ok(Download {
+        content_type: ContentType::parse_flexible(query.format.content_type())
+            .unwrap_or(ContentType::Binary),
+        file_name: export_file_name(cluster, vhost, queue_name, query.format),
+        body: StreamReader::new(Box::pin(chunks)),
+    })
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use rabbitmq_messages_management::{
+    exceptions::{FieldError, ServerError},
+    rabbitmq::filters::{MessageFilter, MessageMatcher},
+};
+use rocket::{
+    form::Form,
+    outcome::Outcome,
+    request::{self, FromRequest},
+    Request,
+};
+
+use super::fail;
+
+/// Query parameters selecting messages, shared by the routes reading messages from a queue.
+#[derive(FromForm, Debug)]
+pub struct FilterQuery {
+    /// Only messages whose routing key matches this glob.
+    routing_key: Option<String>,
+    /// Only messages carrying these headers with these values: `headers[x-tenant]=acme`.
+    headers: HashMap<String, String>,
+    /// Only messages carrying these headers, whatever their value.
+    has_header: Vec<String>,
+    /// Only messages of this content type.
+    content_type: Option<String>,
+    /// Only messages that were (or were not) redelivered.
+    redelivered: Option<bool>,
+    /// Only messages whose payload contains this text.
+    payload_contains: Option<String>,
+    /// Only messages whose payload matches this regular expression.
+    payload_regex: Option<String>,
+    /// Only messages whose JSON payload has a node selected by this JSONPath query.
+    json_path: Option<String>,
+}
+
+impl From<&FilterQuery> for MessageFilter {
+    fn from(query: &FilterQuery) -> Self {
+        MessageFilter {
+            routing_key: query.routing_key.clone(),
+            headers: query
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), serde_json::Value::String(value.clone())))
+                .collect(),
+            has_header: query.has_header.clone(),
+            content_type: query.content_type.clone(),
+            redelivered: query.redelivered,
+            payload_contains: query.payload_contains.clone(),
+            payload_regex: query.payload_regex.clone(),
+            json_path: query.json_path.clone(),
+        }
+    }
+}
+
+/// The filter given in the query string of a request, compiled.
+///
+/// The other query parameters are ignored, so the route can parse them into its own query type.
+pub struct QueryFilter {
+    /// The filter as given.
+    pub filter: MessageFilter,
+    /// The compiled filter.
+    pub matcher: MessageMatcher,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for QueryFilter {
+    type Error = ServerError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let query = match Form::<FilterQuery>::parse_iter(request.query_fields()) {
+            Ok(query) => query,
+            Err(errors) => {
+                return fail(
+                    request,
+                    ServerError::InvalidParameters {
+                        errors: FieldError::from_form_errors(&errors),
+                    },
+                )
+            }
+        };
+        let filter = MessageFilter::from(&query);
+        match filter.compile() {
+            Ok(matcher) => Outcome::Success(QueryFilter { filter, matcher }),
+            Err(error) => fail(request, error),
+        }
+    }
+}
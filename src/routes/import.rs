@@ -0,0 +1,109 @@
+use rabbitmq_messages_management::{
+    config::AppConfig,
+    exceptions::{FieldError, ServerError},
+    import::{
+        ImportDestination, ImportLedger, ImportOptions, ImportReport, ImportTarget,
+        DEFAULT_IMPORT_RATE, MAX_IMPORT_RATE,
+    },
+};
+use rocket::{
+    data::ToByteUnit,
+    form,
+    http::Status,
+    serde::json::Json,
+    tokio::{io::BufReader, task},
+    Data, State,
+};
+
+use super::{
+    audit::AuditParameters,
+    auth::{Authorized, Operator},
+    session::ClusterClient,
+};
+
+/// Query parameters accepted when importing messages.
+#[derive(FromForm, Debug)]
+pub struct ImportParams {
+    /// Exchange to publish every message to, instead of its original exchange.
+    exchange: Option<String>,
+    /// Routing key to publish every message with, instead of its original routing key.
+    routing_key: Option<String>,
+    /// Messages published per second, at most.
+    #[field(default = DEFAULT_IMPORT_RATE, validate = range(1..=MAX_IMPORT_RATE as isize))]
+    rate: u32,
+    /// Checks the file and reports what would be published, without publishing anything.
+    #[field(default = false)]
+    dry_run: bool,
+    /// Publishes messages even if a message with the same id was already imported.
+    #[field(default = false)]
+    force: bool,
+}
+
+/// Publishes the messages of an uploaded NDJSON file, one message per line, as exported by the export
+/// endpoint or written by a purge snapshot.
+///
+/// The response reports what was published and the lines that could not be; its status is the one of
+/// the error the import stopped on, if any.
+///
+/// Messages whose id was already imported with the same destination are skipped, but only for
+/// `import.ledger_ttl_secs` after the last import to it: importing the file again later publishes
+/// every message again. The ids are only remembered across restarts when `import.ledger_path` is set;
+/// they are written to it once the import is over.
+#[post("/<cluster>/exchanges/<vhost>/import?<params..>", data = "<file>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn import(
+    _user: Authorized<Operator>,
+    client: ClusterClient,
+    ledger: &State<ImportLedger>,
+    config: &State<AppConfig>,
+    cluster: &str,
+    vhost: &str,
+    params: form::Result<'_, ImportParams>,
+    file: Data<'_>,
+    audit: AuditParameters<'_>,
+) -> Result<(Status, Json<ImportReport>), ServerError> {
+    let params = params.map_err(|errors| ServerError::InvalidParameters {
+        errors: FieldError::from_form_errors(&errors),
+    })?;
+    let target = ImportTarget {
+        cluster: cluster.to_string(),
+        vhost: vhost.to_string(),
+        destination: ImportDestination {
+            exchange: params.exchange,
+            routing_key: params.routing_key,
+        },
+    };
+    let options = ImportOptions {
+        rate: params.rate,
+        dry_run: params.dry_run,
+        force: params.force,
+        max_bytes: config.import.max_bytes,
+    };
+    // One byte more than allowed is read, to tell a file of exactly `max_bytes` from a larger one.
+    let reader = BufReader::new(file.open((config.import.max_bytes + 1).bytes()));
+    let report = client
+        .import_messages(&target, reader, &options, ledger)
+        .await;
+    if let Err(e) = task::block_in_place(|| ledger.flush()) {
+        log::error!("{}", e);
+    }
+    log::info!(
+        "imported {} messages ({} skipped, {} failed) to vhost {:?} of cluster {:?}{}",
+        report.published,
+        report.skipped,
+        report.failed,
+        vhost,
+        cluster,
+        if report.dry_run { " (dry run)" } else { "" }
+    );
+    // The destination is in the query; the file is summed up by what was done with its lines.
+    audit.record("lines", report.lines);
+    audit.record("published", report.published);
+    audit.record("skipped", report.skipped);
+    audit.record("failed", report.failed);
+    let status = report
+        .error
+        .as_ref()
+        .map_or(Status::Ok, |e| client.error_status(e));
+    Ok((status, Json(report)))
+}
@@ -1,2 +1,43 @@
+use rabbitmq_messages_management::exceptions::ServerError;
+use rocket::{outcome::Outcome, request, Request};
+
+pub mod audit;
+pub mod auth;
+pub mod clusters;
+pub mod exchanges;
+pub mod export;
+pub mod filters;
+pub mod import;
+pub mod moves;
+pub mod purge;
 pub mod queues;
+pub mod request_id;
+pub mod scan;
+pub mod session;
 pub mod vhosts;
+
+/// Fails a request guard, caching the error so the catcher can render it.
+fn fail<T>(request: &Request<'_>, error: ServerError) -> request::Outcome<T, ServerError> {
+    let status = error.status();
+    request.local_cache(|| Some(error.clone()));
+    Outcome::Error((status, error))
+}
+
+/// The error a request guard failed with, if any.
+pub fn guard_error<'r>(request: &'r Request<'_>) -> &'r Option<ServerError> {
+    request.local_cache(|| None::<ServerError>)
+}
+
+/// Reads the value of the dynamic segment `<name>` of the route the request was routed to.
+fn route_param<'r>(request: &'r Request<'_>, name: &str) -> Option<&'r str> {
+    let route = request.route()?;
+    let segment = format!("<{}>", name);
+    let index = route
+        .uri
+        .path()
+        .trim_start_matches(route.uri.base())
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .position(|candidate| candidate == segment)?;
+    request.param::<&str>(index).and_then(Result::ok)
+}
@@ -0,0 +1,44 @@
+use rabbitmq_messages_management::{
+    exceptions::ServerError,
+    rabbitmq::moves::{MoveReport, MoveRequest},
+};
+use rocket::{
+    http::Status,
+    serde::json::{self, Json},
+};
+
+use super::{
+    audit::AuditParameters,
+    auth::{Authorized, Operator},
+    session::ClusterClient,
+};
+
+/// Moves messages from the head of a queue to another queue, or back to where they were
+/// dead-lettered from.
+///
+/// The report of what was moved is returned even when the move stopped early, with the status of the
+/// error it stopped on.
+#[post("/<_>/queues/<vhost>/<queue_name>/move", data = "<request>")]
+pub async fn move_messages(
+    _user: Authorized<Operator>,
+    client: ClusterClient,
+    vhost: &str,
+    queue_name: &str,
+    request: Result<Json<MoveRequest>, json::Error<'_>>,
+    audit: AuditParameters<'_>,
+) -> Result<(Status, Json<MoveReport>), ServerError> {
+    let request = request.map_err(|e| ServerError::InvalidRequest {
+        reason: format!("invalid move request: {}", e),
+    })?;
+    audit.record("count", request.count);
+    audit.record("destination", &request.destination);
+    if !request.filter.is_empty() {
+        audit.record("filter", &request.filter);
+    }
+    let report = client.move_messages(vhost, queue_name, &request).await?;
+    let status = report
+        .error
+        .as_ref()
+        .map_or(Status::Ok, |e| client.error_status(e));
+    Ok((status, Json(report)))
+}
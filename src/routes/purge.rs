@@ -0,0 +1,131 @@
+use rabbitmq_messages_management::{
+    config::AppConfig,
+    exceptions::{FieldError, ServerError},
+    purge::{write_snapshot, PurgeConfirmation, PurgeConfirmations},
+};
+use rocket::{
+    form,
+    serde::{json::Json, Serialize},
+    tokio::task,
+    State,
+};
+
+use super::{
+    auth::{Authorized, Operator},
+    session::ClusterClient,
+};
+
+/// Query parameters accepted when purging a queue.
+#[derive(FromForm, Debug)]
+pub struct PurgeParams {
+    /// Token issued by [`purge_confirmation`].
+    token: String,
+    /// Saves the messages to a file before purging the queue.
+    #[field(default = false)]
+    snapshot: bool,
+}
+
+/// Outcome of a purge.
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct PurgeReport {
+    /// Number of ready messages in the queue just before it was purged.
+    messages_ready: u64,
+    /// File the messages were saved to, if a snapshot was requested.
+    snapshot: Option<String>,
+    /// Number of messages saved in the snapshot.
+    snapshot_messages: Option<usize>,
+}
+
+/// Issues the token confirming a purge, together with the number of messages it would remove.
+#[post("/<cluster>/queues/<vhost>/<queue_name>/contents/confirmation")]
+pub async fn purge_confirmation(
+    user: Authorized<Operator>,
+    client: ClusterClient,
+    confirmations: &State<PurgeConfirmations>,
+    cluster: &str,
+    vhost: &str,
+    queue_name: &str,
+) -> Result<Json<PurgeConfirmation>, ServerError> {
+    let queue = client.get_queue(vhost, queue_name).await?;
+    Ok(Json(confirmations.issue(
+        &user.principal.name,
+        cluster,
+        vhost,
+        queue_name,
+        queue.messages(),
+        queue.messages_ready(),
+    )))
+}
+
+/// Purges a queue, given a token from [`purge_confirmation`].
+///
+/// With `snapshot=true`, the ready messages are first saved to a file in `purge.snapshot_dir` (see
+/// [`ManagementClient::snapshot_queue`]), and the queue is only purged once every message was saved and
+/// the file is written. Messages published between the snapshot and the purge are not saved.
+///
+/// The token is only used up once the purge is sent to the broker, and given back if the broker
+/// fails, so that a purge that did not happen can be retried with the same token.
+///
+/// [`ManagementClient::snapshot_queue`]: rabbitmq_messages_management::client::ManagementClient::snapshot_queue
+#[delete("/<cluster>/queues/<vhost>/<queue_name>/contents?<params..>")]
+#[allow(clippy::too_many_arguments)]
+pub async fn purge(
+    user: Authorized<Operator>,
+    client: ClusterClient,
+    confirmations: &State<PurgeConfirmations>,
+    config: &State<AppConfig>,
+    cluster: &str,
+    vhost: &str,
+    queue_name: &str,
+    params: form::Result<'_, PurgeParams>,
+) -> Result<Json<PurgeReport>, ServerError> {
+    let params = params.map_err(|errors| ServerError::InvalidParameters {
+        errors: FieldError::from_form_errors(&errors),
+    })?;
+    let snapshot_dir = match (params.snapshot, &config.purge.snapshot_dir) {
+        (false, _) => None,
+        (true, Some(dir)) => Some(dir),
+        (true, None) => {
+            return Err(ServerError::InvalidRequest {
+                reason: "snapshots are disabled: purge.snapshot_dir is not set".to_string(),
+            })
+        }
+    };
+    let principal = &user.principal.name;
+    confirmations.check(&params.token, principal, cluster, vhost, queue_name)?;
+
+    let queue = client.get_queue(vhost, queue_name).await?;
+    let mut report = PurgeReport {
+        messages_ready: queue.messages_ready(),
+        snapshot: None,
+        snapshot_messages: None,
+    };
+    if let Some(dir) = snapshot_dir {
+        let messages = client
+            .snapshot_queue(
+                vhost,
+                queue_name,
+                queue.messages_ready(),
+                config.purge.max_snapshot_messages,
+            )
+            .await?;
+        let path =
+            task::block_in_place(|| write_snapshot(dir, cluster, vhost, queue_name, &messages))?;
+        log::info!(
+            "saved {} messages of queue {:?} to {} before purging it",
+            messages.len(),
+            queue_name,
+            path.display()
+        );
+        report.snapshot = Some(path.display().to_string());
+        report.snapshot_messages = Some(messages.len());
+    }
+
+    let redeemed = confirmations.redeem(&params.token, principal, cluster, vhost, queue_name)?;
+    if let Err(e) = client.purge_queue(vhost, queue_name).await {
+        confirmations.reinstate(redeemed);
+        return Err(e);
+    }
+    Ok(Json(report))
+}
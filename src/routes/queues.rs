@@ -1,36 +1,171 @@
-use crate::rabbitmq::queues::{
-    get_messages_from_a_queue, get_queue_for_vhost, Queue, ResponseForQueryingMessages,
+use rabbitmq_messages_management::{
+    auth::Role,
+    exceptions::{FieldError, ServerError},
+    rabbitmq::{
+        content::DecodeBudget,
+        queues::{
+            AckMode, FilteredMessages, MessageRetrievalOptions, PayloadEncoding, Queue,
+            ResponseForQueryingMessages,
+        },
+    },
 };
-use rocket::serde::json::Json;
-
-#[get("/<vhost>")]
-pub async fn queues(vhost: &str) -> Json<Vec<Queue>> {
-    let get_queue_for_vhost = get_queue_for_vhost(vhost).await;
-    match get_queue_for_vhost {
-        Ok(response) => Json(response),
-        Err(e) => {
-            log::error!("{:?}", e.message);
-            Json(vec![])
+use rocket::{form, http::Header, serde::json::Json, tokio::task};
+
+use super::{
+    auth::{Authorized, Viewer},
+    filters::QueryFilter,
+    session::ClusterClient,
+};
+
+/// Header telling how many messages were examined to find the ones returned.
+const SCANNED_MESSAGES_HEADER: &str = "X-Scanned-Messages";
+
+/// Maximum number of messages that can be fetched in one request.
+const MAX_MESSAGE_COUNT: isize = 1000;
+
+/// Query parameters accepted when fetching messages from a queue.
+#[derive(FromForm, Debug)]
+pub struct MessageQuery {
+    /// Number of messages to fetch.
+    #[field(validate = range(1..=MAX_MESSAGE_COUNT))]
+    count: u64,
+    /// What happens to the messages once they have been fetched.
+    #[field(default = AckMode::AckRequeueTrue)]
+    ackmode: AckMode,
+    /// How the payload is encoded in the response.
+    #[field(default = PayloadEncoding::Auto)]
+    encoding: PayloadEncoding,
+    /// If set, payloads larger than this many bytes are truncated.
+    #[field(validate = positive())]
+    truncate: Option<u64>,
+    /// Decompresses payloads according to their `content_encoding`.
+    #[field(default = true)]
+    decode: bool,
+    /// Confirms that a destructive ack mode is intended.
+    #[field(default = false)]
+    destructive: bool,
+}
+
+/// Rejects a parameter that is present but zero.
+pub(super) fn positive<'v>(value: &Option<u64>) -> form::Result<'v, ()> {
+    match value {
+        Some(0) => Err(form::Error::validation("must be at least 1"))?,
+        _ => Ok(()),
+    }
+}
+
+impl From<&MessageQuery> for MessageRetrievalOptions {
+    fn from(query: &MessageQuery) -> Self {
+        MessageRetrievalOptions {
+            count: query.count,
+            ackmode: query.ackmode,
+            encoding: query.encoding,
+            truncate: query.truncate,
         }
     }
 }
 
-#[get("/<vhost>/<queue_name>?<count>")]
+/// Messages returned by [`messages`], with the number of messages examined in the
+/// `X-Scanned-Messages` header.
+#[derive(Responder)]
+pub struct MatchingMessages {
+    /// The messages matching the filters.
+    inner: Json<Vec<ResponseForQueryingMessages>>,
+    /// Number of messages fetched and examined.
+    scanned: Header<'static>,
+}
+
+#[get("/<_>/queues/<vhost>")]
+pub async fn queues(
+    _user: Authorized<Viewer>,
+    client: ClusterClient,
+    vhost: &str,
+) -> Result<Json<Vec<Queue>>, ServerError> {
+    Ok(Json(client.get_queue_for_vhost(vhost).await?))
+}
+
+/// Destructive ack modes (which remove the fetched messages from the queue) are only honoured when
+/// `destructive=true` is passed as well, so that a client cannot drain a queue by accident. They need
+/// the operator role, and so do fetches from a quorum queue with a delivery limit, which counts every
+/// fetch as a delivery and eventually drops or dead-letters the messages fetched too often.
+///
+/// The filters are applied to the `count` fetched messages, so fewer messages may be returned. They
+/// cannot be combined with a destructive ack mode, which would remove the messages that do not match
+/// without returning them.
+///
+/// Compressed payloads are returned decompressed unless `decode=false` is passed (see
+/// [`ResponseForQueryingMessages::decode_content`]). Each payload is decompressed once, for the filters
+/// and the response, within one [`DecodeBudget`] for the whole request.
+#[get("/<_>/queues/<vhost>/<queue_name>?<query..>")]
 pub async fn messages(
+    user: Authorized<Viewer>,
+    client: ClusterClient,
     vhost: &str,
     queue_name: &str,
-    count: &str,
-) -> Json<Vec<ResponseForQueryingMessages>> {
-    let number: u64 = count
-        .parse::<u64>()
-        .expect("Failed to parse string to a valid count");
-    let messages =
-        get_messages_from_a_queue(vhost.to_string(), queue_name.to_string(), number).await;
-    match messages {
-        Ok(response) => Json(response),
-        Err(e) => {
-            log::error!("{:?}", e.message);
-            Json(vec![])
+    query: form::Result<'_, MessageQuery>,
+    filter: QueryFilter,
+) -> Result<MatchingMessages, ServerError> {
+    let query = query.map_err(|errors| ServerError::InvalidParameters {
+        errors: FieldError::from_form_errors(&errors),
+    })?;
+    if query.ackmode.is_destructive() && !query.destructive {
+        return Err(ServerError::InvalidParameters {
+            errors: vec![FieldError {
+                field: "destructive".to_string(),
+                message: format!(
+                    "ack mode {:?} removes messages from the queue; pass destructive=true to confirm",
+                    query.ackmode
+                ),
+            }],
+        });
+    }
+    if query.ackmode.is_destructive() && !filter.filter.is_empty() {
+        return Err(ServerError::InvalidParameters {
+            errors: vec![FieldError {
+                field: "ackmode".to_string(),
+                message: "filters cannot be combined with an ack mode that removes messages, as \
+                          the messages not matching would be lost"
+                    .to_string(),
+            }],
+        });
+    }
+    if query.ackmode.is_destructive() {
+        user.require(Role::Operator, "remove messages from a queue")?;
+    } else if user.role < Role::Operator {
+        let queue = client.get_queue(vhost, queue_name).await?;
+        if queue.delivery_limit().is_some() {
+            user.require(
+                Role::Operator,
+                "fetch messages from a queue with a delivery limit, where each fetch counts as a \
+                 delivery",
+            )?;
         }
     }
+
+    let fetched = client
+        .get_messages_from_a_queue(
+            vhost.to_string(),
+            queue_name.to_string(),
+            &MessageRetrievalOptions::from(&query),
+        )
+        .await?;
+    // Decompressing and matching payloads is CPU-bound.
+    let (messages, scanned) = task::block_in_place(|| {
+        let mut budget = DecodeBudget::default();
+        let filtered = FilteredMessages::filter(fetched, &filter.matcher, &mut budget);
+        let messages = if query.decode {
+            filtered
+                .messages
+                .into_iter()
+                .map(|message| message.decode_content(query.encoding, query.truncate, &mut budget))
+                .collect()
+        } else {
+            filtered.messages
+        };
+        (messages, filtered.scanned)
+    });
+    Ok(MatchingMessages {
+        inner: Json(messages),
+        scanned: Header::new(SCANNED_MESSAGES_HEADER, scanned.to_string()),
+    })
 }
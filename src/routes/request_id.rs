@@ -0,0 +1,57 @@
+use rabbitmq_messages_management::logging::{next_request_id, RequestId};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    http::Header,
+    Data, Request, Response,
+};
+
+/// Header carrying the id of a request, both ways.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Longest request id accepted from a client.
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+
+/// Fairing giving each request an id, logged with the management API calls it causes and returned in
+/// the `X-Request-Id` response header.
+///
+/// A client may pick the id by sending the header itself; ids that are too long or contain anything
+/// but letters, digits, `-`, `_` and `.` are replaced by a generated one, so they are safe to log.
+pub struct RequestIds;
+
+#[rocket::async_trait]
+impl Fairing for RequestIds {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request ids",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, request: &mut Request<'_>, _: &mut Data<'_>) {
+        let id = request
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_REQUEST_ID_LENGTH
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            })
+            .map(str::to_string)
+            .unwrap_or_else(next_request_id);
+        request.local_cache(|| RequestId(id));
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        response.set_header(Header::new(
+            REQUEST_ID_HEADER,
+            request_id(request).to_string(),
+        ));
+    }
+}
+
+/// The id of the request.
+pub fn request_id<'r>(request: &'r Request<'_>) -> &'r str {
+    &request.local_cache(|| RequestId(next_request_id())).0
+}
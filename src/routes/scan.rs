@@ -0,0 +1,111 @@
+use rabbitmq_messages_management::{
+    exceptions::{ErrorBody, FieldError, ServerError},
+    rabbitmq::{
+        queues::PayloadEncoding,
+        scan::{ScanOptions, MAX_SCAN_BATCH_SIZE, MAX_SCAN_LIMIT, MAX_SCAN_MATCHES},
+    },
+};
+use rocket::{
+    form,
+    response::stream::{Event, EventStream},
+    tokio::task,
+};
+
+use super::{
+    auth::{Authorized, Operator},
+    filters::QueryFilter,
+    queues::positive,
+    session::ClusterClient,
+};
+
+/// Query parameters accepted when scanning a queue, besides the message filters.
+#[derive(FromForm, Debug)]
+pub struct ScanQuery {
+    /// Number of messages fetched past the ones already examined, at each step.
+    #[field(default = 100, validate = range(1..=MAX_SCAN_BATCH_SIZE as isize))]
+    batch_size: u64,
+    /// Maximum number of messages to examine.
+    #[field(default = 10_000, validate = range(1..=MAX_SCAN_LIMIT as isize))]
+    limit: u64,
+    /// The scan stops once this many messages matched.
+    #[field(default = 100, validate = range(1..=MAX_SCAN_MATCHES as isize))]
+    max_matches: u64,
+    /// How the payload is encoded in the matching messages.
+    #[field(default = PayloadEncoding::Auto)]
+    encoding: PayloadEncoding,
+    /// If set, payloads larger than this many bytes are truncated.
+    #[field(validate = positive())]
+    truncate: Option<u64>,
+    /// Decompresses payloads according to their `content_encoding`.
+    #[field(default = true)]
+    decode: bool,
+}
+
+impl From<&ScanQuery> for ScanOptions {
+    fn from(query: &ScanQuery) -> Self {
+        ScanOptions {
+            batch_size: query.batch_size,
+            limit: query.limit,
+            max_matches: query.max_matches,
+            encoding: query.encoding,
+            truncate: query.truncate,
+            decode: query.decode,
+        }
+    }
+}
+
+/// Scans a queue past the first messages, streaming server-sent events as it goes: `warning` events
+/// on the side effects of the scan, a `match` event per matching message, a `progress` event after
+/// each batch, then `done` with the final progress, or `error` if a batch could not be fetched.
+///
+/// Messages are only fetched with requeue, so none is removed, but each fetch flags them as
+/// redelivered, and counts against the delivery limit of a quorum queue, so scans are for operators.
+/// Compressed payloads are sent decompressed unless `decode=false` is passed.
+#[get("/<_>/queues/<vhost>/<queue_name>/scan?<query..>")]
+pub async fn scan(
+    _user: Authorized<Operator>,
+    client: ClusterClient,
+    vhost: &str,
+    queue_name: &str,
+    query: form::Result<'_, ScanQuery>,
+    filter: QueryFilter,
+) -> Result<EventStream![], ServerError> {
+    let query = query.map_err(|errors| ServerError::InvalidParameters {
+        errors: FieldError::from_form_errors(&errors),
+    })?;
+    let mut scan = client
+        .scan_queue(vhost, queue_name, filter.matcher, ScanOptions::from(&query))
+        .await?;
+
+    let queue_name = queue_name.to_string();
+    let request_id = client.request_id().to_string();
+    Ok(EventStream! {
+        loop {
+            for warning in scan.take_warnings() {
+                yield Event::data(warning).event("warning");
+            }
+            match scan.next_batch().await {
+                Ok(Some(fetched)) => {
+                    let matches = task::block_in_place(|| scan.absorb(fetched));
+                    for message in matches {
+                        yield Event::json(&message).event("match");
+                    }
+                    yield Event::json(scan.progress()).event("progress");
+                }
+                Ok(None) => break,
+                Err(error) => {
+                    log::warn!(
+                        "request_id={} scan of queue {:?} failed after {} messages: {}",
+                        request_id,
+                        queue_name,
+                        scan.progress().scanned,
+                        error
+                    );
+                    yield Event::json(&ErrorBody::from(&error)).event("error");
+                    return;
+                }
+            }
+        }
+        yield Event::json(scan.progress()).event("done");
+    })
+}
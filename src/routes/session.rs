@@ -0,0 +1,223 @@
+use std::ops::Deref;
+
+use rabbitmq_messages_management::{
+    client::ManagementClient,
+    clusters::Clusters,
+    config::AppConfig,
+    exceptions::{ServerError, UserCredentials},
+    rabbitmq::users::WhoAmI,
+};
+use rocket::{
+    http::{Cookie, CookieJar, SameSite, Status},
+    outcome::Outcome,
+    request::{self, FromRequest},
+    serde::{json::Json, Deserialize, Serialize},
+    time::{Duration, OffsetDateTime},
+    Request, State,
+};
+
+use super::{
+    auth::{Authenticated, Authorized, Viewer},
+    fail,
+    request_id::request_id,
+};
+
+/// Credentials a user logs in to a cluster with.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct SessionCredentials {
+    /// RabbitMQ username.
+    username: String,
+    /// RabbitMQ password.
+    password: String,
+}
+
+/// The credentials of a session on a cluster, kept in an encrypted private cookie.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "rocket::serde")]
+struct StoredCredentials {
+    /// Name of the principal who logged in, the only one the credentials are used for.
+    principal: String,
+    /// Unix timestamp the session expires at.
+    expires: i64,
+    /// The credentials.
+    credentials: SessionCredentials,
+}
+
+/// Name of the private cookie holding the credentials for a cluster.
+fn cookie_name(cluster: &str) -> String {
+    format!("rabbitmq_session_{}", cluster)
+}
+
+/// Reads the credentials stored for a cluster, if `principal` logged in to it and the session has not
+/// expired.
+fn session_credentials(
+    cookies: &CookieJar<'_>,
+    cluster: &str,
+    principal: &str,
+) -> Option<SessionCredentials> {
+    let cookie = cookies.get_private(&cookie_name(cluster))?;
+    let stored: StoredCredentials = rocket::serde::json::from_str(cookie.value()).ok()?;
+    (stored.principal == principal && stored.expires > OffsetDateTime::now_utc().unix_timestamp())
+        .then_some(stored.credentials)
+}
+
+/// Management API client of the cluster named by the first path segment of the route.
+///
+/// When `credential_passthrough` is enabled, the client authenticates with the credentials the user
+/// logged in with (see [`login`]) instead of the configured ones, and requests without a session are
+/// rejected with `401 login_required`, as are requests of another principal than the one who logged
+/// in, or made once the session expired. The management API rejecting these credentials is then
+/// returned as a `401` or `403` rather than as a gateway error (see [`ServerError::status_for`]).
+pub struct ClusterClient {
+    /// Client of the cluster, with the credentials of the user if they are passed through.
+    client: ManagementClient,
+    /// Id of the request the client serves.
+    request_id: String,
+    /// Whether the client uses the credentials of the user.
+    user_credentials: bool,
+}
+
+impl ClusterClient {
+    /// Id of the request the client serves, to log with what it does.
+    pub fn request_id(&self) -> &str {
+        &self.request_id
+    }
+
+    /// Logs an error of this client reported in a response body, and returns its HTTP status.
+    pub fn error_status(&self, error: &ServerError) -> Status {
+        error.log(&self.request_id);
+        error.status_for(self.user_credentials)
+    }
+}
+
+impl Deref for ClusterClient {
+    type Target = ManagementClient;
+
+    fn deref(&self) -> &Self::Target {
+        &self.client
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClusterClient {
+    type Error = ServerError;
+
+    async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        let (Some(clusters), Some(config)) = (
+            request.rocket().state::<Clusters>(),
+            request.rocket().state::<AppConfig>(),
+        ) else {
+            return fail(
+                request,
+                ServerError::Configuration {
+                    reason: "clusters are not configured".to_string(),
+                },
+            );
+        };
+        let cluster = request
+            .param::<&str>(0)
+            .and_then(Result::ok)
+            .unwrap_or_default();
+        let request_id = request_id(request).to_string();
+        let client = match clusters.client(cluster) {
+            Ok(client) => client.with_request_id(&request_id),
+            Err(e) => return fail(request, e),
+        };
+        if !config.credential_passthrough {
+            return Outcome::Success(ClusterClient {
+                client,
+                request_id,
+                user_credentials: false,
+            });
+        }
+        let Authenticated(principal) = match request.guard::<Authenticated>().await {
+            Outcome::Success(authenticated) => authenticated,
+            Outcome::Error(error) => return Outcome::Error(error),
+            Outcome::Forward(status) => return Outcome::Forward(status),
+        };
+        match session_credentials(request.cookies(), cluster, &principal.name) {
+            Some(credentials) => {
+                request.local_cache(|| UserCredentials(true));
+                Outcome::Success(ClusterClient {
+                    client: client.with_credentials(&credentials.username, &credentials.password),
+                    request_id,
+                    user_credentials: true,
+                })
+            }
+            None => fail(
+                request,
+                ServerError::LoginRequired {
+                    cluster: cluster.to_string(),
+                },
+            ),
+        }
+    }
+}
+
+/// Logs in to a cluster with the user's own RabbitMQ credentials.
+///
+/// The credentials are checked against `/api/whoami` before being stored in an encrypted, HTTP-only
+/// cookie valid for `auth.session_ttl_secs`, like the session on this server, and are forwarded on
+/// every management API call made for the cluster afterwards by the principal who logged in.
+#[post("/<cluster>/session", data = "<credentials>")]
+pub async fn login(
+    user: Authorized<Viewer>,
+    clusters: &State<Clusters>,
+    config: &State<AppConfig>,
+    cookies: &CookieJar<'_>,
+    cluster: &str,
+    credentials: Json<SessionCredentials>,
+) -> Result<Json<WhoAmI>, ServerError> {
+    if !config.credential_passthrough {
+        return Err(ServerError::InvalidRequest {
+            reason: "credential pass-through is disabled".to_string(),
+        });
+    }
+    let whoami = clusters
+        .client(cluster)?
+        .with_credentials(&credentials.username, &credentials.password)
+        .whoami()
+        .await
+        .map_err(|e| match e.is_unauthorized() {
+            true => ServerError::Unauthenticated {
+                reason: format!("cluster {:?} rejected the credentials", cluster),
+            },
+            false => e,
+        })?;
+
+    let ttl = Duration::seconds(config.auth.session_ttl_secs as i64);
+    let stored = StoredCredentials {
+        principal: user.principal.name,
+        expires: (OffsetDateTime::now_utc() + ttl).unix_timestamp(),
+        credentials: credentials.into_inner(),
+    };
+    let value =
+        rocket::serde::json::to_string(&stored).map_err(|e| ServerError::Serialization {
+            reason: e.to_string(),
+        })?;
+    cookies.add_private(
+        Cookie::build((cookie_name(cluster), value))
+            .path("/clusters")
+            .max_age(ttl)
+            .http_only(true)
+            .same_site(SameSite::Strict),
+    );
+    Ok(Json(whoami))
+}
+
+/// Describes the user requests to a cluster are made as.
+#[get("/<_>/session")]
+pub async fn session(
+    _user: Authorized<Viewer>,
+    client: ClusterClient,
+) -> Result<Json<WhoAmI>, ServerError> {
+    Ok(Json(client.whoami().await?))
+}
+
+/// Logs out of a cluster, forgetting the stored credentials.
+#[delete("/<cluster>/session")]
+pub fn logout(_user: Authorized<Viewer>, cookies: &CookieJar<'_>, cluster: &str) -> Status {
+    cookies.remove_private(Cookie::build(cookie_name(cluster)).path("/clusters"));
+    Status::NoContent
+}
@@ -1,14 +1,30 @@
-use rocket::serde::json::Json;
+use rabbitmq_messages_management::{
+    auth::{Authorizer, Role},
+    exceptions::ServerError,
+    rabbitmq::vhosts::ResponseForQueryingVhosts,
+};
+use rocket::{serde::json::Json, State};
 
-use crate::rabbitmq::vhosts::{get_vhosts, ResponseForQueryingVhosts};
+use super::{
+    auth::{Authorized, Viewer},
+    session::ClusterClient,
+};
 
-#[get("/")]
-pub async fn vhosts() -> Json<Vec<ResponseForQueryingVhosts>> {
-    match get_vhosts().await {
-        Ok(response) => Json(response),
-        Err(e) => {
-            log::error!("{:?}", e.message);
-            Json(vec![])
-        }
-    }
+/// Lists the vhosts of a cluster the principal may view.
+#[get("/<cluster>/vhosts")]
+pub async fn vhosts(
+    user: Authorized<Viewer>,
+    authorizer: &State<Authorizer>,
+    client: ClusterClient,
+    cluster: &str,
+) -> Result<Json<Vec<ResponseForQueryingVhosts>>, ServerError> {
+    let vhosts = client.get_vhosts().await?;
+    Ok(Json(
+        vhosts
+            .into_iter()
+            .filter(|vhost| {
+                authorizer.role(&user.principal.name, cluster, vhost.name()) >= Some(Role::Viewer)
+            })
+            .collect(),
+    ))
 }
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, fs, io::Write, path::PathBuf};
+
+    use rabbitmq_messages_management::{
+        audit::{AuditLog, AuditOutcome, AuditQuery, AuditRecord},
+        config::AuditConfig,
+    };
+    use time::{Duration, OffsetDateTime};
+
+    fn audit_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "viewer-audit-{}-{}.jsonl",
+            name,
+            std::process::id()
+        ));
+        for suffix in ["", ".1", ".2", ".3"] {
+            let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+        path
+    }
+
+    fn record(principal: &str, queue: &str, time: OffsetDateTime) -> AuditRecord {
+        AuditRecord {
+            time,
+            principal: Some(principal.to_string()),
+            operation: "messages".to_string(),
+            request: format!("GET /clusters/default/queues/%2F/{}", queue),
+            cluster: Some("default".to_string()),
+            vhost: Some("/".to_string()),
+            queue: Some(queue.to_string()),
+            parameters: BTreeMap::from([("count".to_string(), "1".to_string())]),
+            outcome: AuditOutcome::Success,
+            status: 200,
+            error: None,
+        }
+    }
+
+    fn query(limit: usize) -> AuditQuery {
+        AuditQuery {
+            limit,
+            ..AuditQuery::default()
+        }
+    }
+
+    #[test]
+    fn test_disabled_without_path() {
+        assert!(AuditLog::open(&AuditConfig::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_query_filters_records() {
+        let path = audit_path("filters");
+        let log = AuditLog::open(&AuditConfig {
+            path: Some(path.clone()),
+            ..AuditConfig::default()
+        })
+        .unwrap()
+        .unwrap();
+        let now = OffsetDateTime::now_utc();
+        log.append(&record("alice", "orders", now - Duration::hours(2)))
+            .unwrap();
+        log.append(&record("bob", "orders", now - Duration::hours(1)))
+            .unwrap();
+        log.append(&record("alice", "payments", now)).unwrap();
+
+        let alice = log
+            .query(&AuditQuery {
+                principal: Some("alice".to_string()),
+                ..query(10)
+            })
+            .unwrap();
+        assert_eq!(alice.len(), 2);
+        assert_eq!(alice[1], record("alice", "payments", now));
+
+        let recent_orders = log
+            .query(&AuditQuery {
+                since: Some(now - Duration::minutes(90)),
+                queue: Some("orders".to_string()),
+                ..query(10)
+            })
+            .unwrap();
+        assert_eq!(recent_orders.len(), 1);
+        assert_eq!(recent_orders[0].principal.as_deref(), Some("bob"));
+
+        let latest = log.query(&query(1)).unwrap();
+        assert_eq!(latest[0].queue.as_deref(), Some("payments"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let path = audit_path("rotation");
+        let log = AuditLog::open(&AuditConfig {
+            path: Some(path.clone()),
+            max_bytes: 1,
+            max_files: 2,
+        })
+        .unwrap()
+        .unwrap();
+        let now = OffsetDateTime::now_utc();
+        for queue in ["q1", "q2", "q3", "q4"] {
+            log.append(&record("alice", queue, now)).unwrap();
+        }
+
+        let queues: Vec<_> = log
+            .query(&query(10))
+            .unwrap()
+            .into_iter()
+            .map(|record| record.queue.unwrap())
+            .collect();
+        assert_eq!(queues, ["q2", "q3", "q4"]);
+        assert!(!PathBuf::from(format!("{}.3", path.display())).exists());
+        for suffix in ["", ".1", ".2"] {
+            fs::remove_file(format!("{}{}", path.display(), suffix)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_query_skips_corrupt_lines() {
+        let path = audit_path("corrupt");
+        let log = AuditLog::open(&AuditConfig {
+            path: Some(path.clone()),
+            ..AuditConfig::default()
+        })
+        .unwrap()
+        .unwrap();
+        log.append(&record("alice", "orders", OffsetDateTime::now_utc()))
+            .unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"time\": \"2024-01-01T0\n")
+            .unwrap();
+
+        assert_eq!(log.query(&query(10)).unwrap().len(), 1);
+        fs::remove_file(path).unwrap();
+    }
+}
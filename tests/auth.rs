@@ -0,0 +1,202 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::{
+        auth::{parse_users, AuthMethod, Authenticator, Authorizer, Principal, Role},
+        config::{ApiTokenConfig, AuthConfig, RoleGrant},
+        exceptions::ServerError,
+    };
+
+    fn token(name: &str, token: &str) -> ApiTokenConfig {
+        ApiTokenConfig {
+            name: name.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_users_skips_comments_and_blank_lines() {
+        let hash = bcrypt::hash("secret", 4).unwrap();
+        let users = parse_users(&format!("# viewers\n\nalice:{0}\nbob:{0}\n", hash)).unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!(users["bob"], hash);
+    }
+
+    #[test]
+    fn test_parse_users_rejects_malformed_lines() {
+        let hash = bcrypt::hash("secret", 4).unwrap();
+        for contents in [
+            "alice".to_string(),
+            "alice:$apr1$abc$def".to_string(),
+            format!("alice:{0}\nalice:{0}", hash),
+        ] {
+            assert!(parse_users(&contents).is_err(), "{}", contents);
+        }
+    }
+
+    #[test]
+    fn test_authentication_is_disabled_without_tokens_or_users() {
+        let authenticator = Authenticator::from_config(&AuthConfig::default()).unwrap();
+        assert!(!authenticator.is_enabled());
+    }
+
+    #[test]
+    fn test_authenticate_token() {
+        let authenticator = Authenticator::from_config(&AuthConfig {
+            api_tokens: vec![token("ci", "first-token"), token("ops", "second-token")],
+            ..AuthConfig::default()
+        })
+        .unwrap();
+
+        assert!(authenticator.is_enabled());
+        assert_eq!(
+            authenticator.authenticate_token("second-token"),
+            Some(Principal::new("ops", AuthMethod::Token))
+        );
+        assert_eq!(authenticator.authenticate_token("second"), None);
+        assert_eq!(authenticator.authenticate_token(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_password_against_users_file() {
+        let path = std::env::temp_dir().join(format!("viewer-users-{}", std::process::id()));
+        std::fs::write(
+            &path,
+            format!("alice:{}\n", bcrypt::hash("secret", 4).unwrap()),
+        )
+        .unwrap();
+        let authenticator = Authenticator::from_config(&AuthConfig {
+            users_file: Some(path.clone()),
+            ..AuthConfig::default()
+        });
+        std::fs::remove_file(&path).unwrap();
+        let authenticator = authenticator.unwrap();
+
+        assert_eq!(
+            authenticator
+                .authenticate_password("alice", "secret", AuthMethod::Basic)
+                .await,
+            Some(Principal::new("alice", AuthMethod::Basic))
+        );
+        assert_eq!(
+            authenticator
+                .authenticate_password("alice", "wrong", AuthMethod::Basic)
+                .await,
+            None
+        );
+        assert_eq!(
+            authenticator
+                .authenticate_password("bob", "secret", AuthMethod::Basic)
+                .await,
+            None
+        );
+        // Unknown usernames are checked against a dummy hash, whose password must not log in.
+        assert_eq!(
+            authenticator
+                .authenticate_password("bob", "not-a-user", AuthMethod::Basic)
+                .await,
+            None
+        );
+    }
+
+    #[test]
+    fn test_missing_users_file_is_a_configuration_error() {
+        let result = Authenticator::from_config(&AuthConfig {
+            users_file: Some("/nonexistent/users".into()),
+            ..AuthConfig::default()
+        });
+        assert!(matches!(result, Err(ServerError::Configuration { .. })));
+    }
+
+    fn grant(principal: &str, cluster: &str, vhost: &str, role: Role) -> RoleGrant {
+        RoleGrant {
+            principal: principal.to_string(),
+            cluster: cluster.to_string(),
+            vhost: vhost.to_string(),
+            role,
+        }
+    }
+
+    #[test]
+    fn test_roles_are_ordered() {
+        assert!(Role::Viewer < Role::Operator);
+        assert!(Role::Operator < Role::Admin);
+    }
+
+    #[test]
+    fn test_highest_matching_grant_wins() {
+        let authorizer = Authorizer::from_config(&AuthConfig {
+            default_role: None,
+            grants: vec![
+                grant("*", "*", "*", Role::Viewer),
+                grant("alice", "prod", "orders-*", Role::Operator),
+                grant("ops-*", "*", "*", Role::Admin),
+            ],
+            ..AuthConfig::default()
+        });
+
+        assert_eq!(
+            authorizer.role("bob", "prod", "orders-eu"),
+            Some(Role::Viewer)
+        );
+        assert_eq!(
+            authorizer.role("alice", "prod", "orders-eu"),
+            Some(Role::Operator)
+        );
+        assert_eq!(
+            authorizer.role("alice", "dev", "orders-eu"),
+            Some(Role::Viewer)
+        );
+        assert_eq!(authorizer.role("ops-oncall", "dev", "/"), Some(Role::Admin));
+        assert_eq!(
+            authorizer.cluster_role("alice", "prod"),
+            Some(Role::Operator)
+        );
+    }
+
+    #[test]
+    fn test_no_default_role_denies_ungranted_access() {
+        let authorizer = Authorizer::from_config(&AuthConfig {
+            default_role: None,
+            grants: vec![grant("alice", "prod", "orders", Role::Viewer)],
+            ..AuthConfig::default()
+        });
+
+        assert_eq!(authorizer.role("alice", "prod", "payments"), None);
+        assert_eq!(authorizer.role("bob", "prod", "orders"), None);
+        assert_eq!(authorizer.cluster_role("alice", "prod"), Some(Role::Viewer));
+        assert_eq!(authorizer.cluster_role("alice", "dev"), None);
+    }
+
+    #[test]
+    fn test_global_role_needs_the_role_on_every_vhost_of_every_cluster() {
+        let authorizer = Authorizer::from_config(&AuthConfig {
+            default_role: Some(Role::Viewer),
+            grants: vec![
+                grant("alice", "*", "*", Role::Admin),
+                grant("bob", "prod", "*", Role::Admin),
+                grant("carol", "*", "orders", Role::Admin),
+            ],
+            ..AuthConfig::default()
+        });
+        let clusters = ["prod", "dev"];
+
+        assert_eq!(authorizer.global_role("alice", clusters), Some(Role::Admin));
+        assert_eq!(authorizer.global_role("bob", clusters), Some(Role::Viewer));
+        assert_eq!(authorizer.global_role("bob", ["prod"]), Some(Role::Admin));
+        // A grant on some vhosts only is not a role on the whole cluster.
+        assert_eq!(authorizer.cluster_role("carol", "prod"), Some(Role::Admin));
+        assert_eq!(
+            authorizer.global_role("carol", clusters),
+            Some(Role::Viewer)
+        );
+        // Without clusters, only the default role counts.
+        assert_eq!(authorizer.global_role("dave", []), Some(Role::Viewer));
+
+        let denying = Authorizer::from_config(&AuthConfig {
+            default_role: None,
+            grants: vec![grant("alice", "prod", "*", Role::Admin)],
+            ..AuthConfig::default()
+        });
+        assert_eq!(denying.global_role("alice", clusters), None);
+    }
+}
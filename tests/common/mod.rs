@@ -0,0 +1,41 @@
+//! Helpers shared by the integration tests.
+
+// Each test crate only uses some of the helpers.
+#![allow(dead_code)]
+
+use rabbitmq_messages_management::rabbitmq::queues::{
+    RabbitMQMessage, ResponseForQueryingMessages,
+};
+use serde_json::{json, Value};
+
+/// A message as returned by the management API: an empty JSON object routed to `orders` through the
+/// default exchange, with `fields` replacing its top-level fields. Unless given, `payload_bytes` is the
+/// length of the payload.
+pub fn message_json(fields: Value) -> Value {
+    let mut message = json!({
+        "redelivered": false,
+        "exchange": "",
+        "routing_key": "orders",
+        "message_count": 0,
+        "properties": {},
+        "payload": "{}",
+        "payload_encoding": "string"
+    });
+    for (name, value) in fields
+        .as_object()
+        .expect("message fields must be an object")
+    {
+        message[name] = value.clone();
+    }
+    if message.get("payload_bytes").is_none() {
+        let length = message["payload"].as_str().map_or(0, str::len);
+        message["payload_bytes"] = json!(length);
+    }
+    message
+}
+
+/// The message described by [`message_json`].
+pub fn message(fields: Value) -> ResponseForQueryingMessages {
+    let message: RabbitMQMessage = serde_json::from_value(message_json(fields)).unwrap();
+    ResponseForQueryingMessages::from(message)
+}
@@ -0,0 +1,167 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::{
+        auth::Role, clusters::Clusters, config::AppConfig, exceptions::ServerError,
+    };
+    use rocket::figment::{
+        providers::{Format, Toml},
+        Figment,
+    };
+
+    fn figment(toml: &str) -> Figment {
+        Figment::new().merge(Toml::string(toml))
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let config = AppConfig::from_figment(&figment(
+            r#"
+            static_dir = "./client/build"
+            port = 8088
+
+            [management]
+            root = "https://rabbitmq.example.com:15671/"
+            username = "viewer"
+            password = "secret"
+            timeout_secs = 10
+            "#,
+        ))
+        .unwrap();
+
+        let clusters = config.all_clusters();
+        assert_eq!(config.port, 8088);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].id, "default");
+        assert_eq!(clusters[0].management.timeout_secs, 10);
+        assert_eq!(clusters[0].management.connect_timeout_secs, 5);
+        assert!(!clusters[0].management.tls.accept_invalid_certs);
+        assert!(!format!("{:?}", config).contains("secret"));
+    }
+
+    #[test]
+    fn test_missing_management_settings_are_rejected() {
+        let result = AppConfig::from_figment(&figment("port = 8088"));
+        assert!(matches!(result, Err(ServerError::Configuration { .. })));
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        for management in [
+            r#"root = "localhost:15672"
+            username = "guest"
+            password = "guest""#,
+            r#"root = "http://localhost:15672"
+            username = ""
+            password = "guest""#,
+            r#"root = "http://localhost:15672"
+            username = "guest"
+            password = "guest"
+            timeout_secs = 0"#,
+            r#"root = "http://localhost:15672"
+            username = "guest"
+            password = "guest"
+            tls = { client_certificate = "/nonexistent/client.pem" }"#,
+        ] {
+            let result =
+                AppConfig::from_figment(&figment(&format!("[management]\n{}", management)));
+            assert!(
+                matches!(result, Err(ServerError::Configuration { .. })),
+                "{} should be rejected",
+                management
+            );
+        }
+    }
+
+    #[test]
+    fn test_multiple_clusters() {
+        let config = AppConfig::from_figment(&figment(
+            r#"
+            [[clusters]]
+            id = "dev"
+            name = "Development"
+            root = "http://rabbitmq.dev:15672"
+            username = "guest"
+            password = "guest"
+
+            [[clusters]]
+            id = "prod"
+            root = "https://rabbitmq.prod:15671"
+            username = "viewer"
+            password = "secret"
+            timeout_secs = 5
+            "#,
+        ))
+        .unwrap();
+
+        let clusters = Clusters::from_config(&config).unwrap();
+        let summaries = serde_json::to_value(clusters.summaries()).unwrap();
+        assert_eq!(summaries[0]["id"], "dev");
+        assert_eq!(summaries[0]["name"], "Development");
+        assert_eq!(summaries[1]["name"], "prod");
+        assert_eq!(
+            clusters.client("prod").unwrap().root(),
+            "https://rabbitmq.prod:15671"
+        );
+        assert!(matches!(
+            clusters.client("staging"),
+            Err(ServerError::UnknownCluster { .. })
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_or_invalid_cluster_ids_are_rejected() {
+        let cluster = |id: &str| {
+            format!(
+                "[[clusters]]\nid = {:?}\nroot = \"http://localhost:15672\"\nusername = \"guest\"\npassword = \"guest\"\n",
+                id
+            )
+        };
+        for toml in [
+            format!("{}{}", cluster("dev"), cluster("dev")),
+            cluster("dev/eu"),
+            cluster(""),
+        ] {
+            let result = AppConfig::from_figment(&figment(&toml));
+            assert!(matches!(result, Err(ServerError::Configuration { .. })));
+        }
+    }
+
+    #[test]
+    fn test_auth_settings() {
+        let management = "[management]\nroot = \"http://localhost:15672\"\nusername = \"guest\"\npassword = \"guest\"\n";
+        let config = AppConfig::from_figment(&figment(&format!(
+            "{}[auth]\napi_tokens = [{{ name = \"ci\", token = \"s3cr3t\" }}]\n",
+            management
+        )))
+        .unwrap();
+        assert!(config.auth.is_enabled());
+        assert_eq!(config.auth.session_ttl_secs, 8 * 60 * 60);
+        assert!(!format!("{:?}", config).contains("s3cr3t"));
+        assert_eq!(config.auth.default_role, Some(Role::Viewer));
+
+        let config = AppConfig::from_figment(&figment(&format!(
+            "{}[auth]\ndefault_role = \"none\"\n[[auth.grants]]\nprincipal = \"alice\"\nrole = \"operator\"\n",
+            management
+        )))
+        .unwrap();
+        assert_eq!(config.auth.default_role, None);
+        assert_eq!(config.auth.grants[0].vhost, "*");
+        assert_eq!(config.auth.grants[0].role, Role::Operator);
+
+        for auth in [
+            "api_tokens = [{ name = \"ci\", token = \"a\" }, { name = \"ci\", token = \"b\" }]",
+            "api_tokens = [{ name = \"ci\", token = \"\" }]",
+            "users_file = \"/nonexistent/users\"",
+            "session_ttl_secs = 0",
+            "default_role = \"root\"",
+        ] {
+            let result =
+                AppConfig::from_figment(&figment(&format!("{}[auth]\n{}\n", management, auth)));
+            assert!(
+                matches!(result, Err(ServerError::Configuration { .. })),
+                "{}",
+                auth
+            );
+        }
+    }
+}
@@ -0,0 +1,258 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use base64::prelude::*;
+    use flate2::{
+        write::{DeflateEncoder, GzEncoder, ZlibEncoder},
+        Compression,
+    };
+    use rabbitmq_messages_management::rabbitmq::{
+        content::{ContentEncoding, DecodeBudget},
+        filters::MessageFilter,
+        queues::{FilteredMessages, PayloadEncoding, ResponseForQueryingMessages},
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    const CONTENT: &[u8] = br#"{"order": {"id": 7, "items": ["tea", "cups"]}}"#;
+
+    fn gzip(content: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(content).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn compress(encoding: ContentEncoding, content: &[u8]) -> Vec<u8> {
+        match encoding {
+            ContentEncoding::Gzip => gzip(content),
+            ContentEncoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+                encoder.write_all(content).unwrap();
+                encoder.finish().unwrap()
+            }
+            ContentEncoding::Brotli => {
+                let mut output = vec![];
+                let mut encoder = brotli::CompressorWriter::new(&mut output, 4096, 5, 22);
+                encoder.write_all(content).unwrap();
+                drop(encoder);
+                output
+            }
+            ContentEncoding::Zstd => zstd::encode_all(content, 0).unwrap(),
+        }
+    }
+
+    /// A message as fetched with `encoding=auto`, compressed payloads being base64 encoded.
+    fn message(
+        content_encoding: &str,
+        payload: &[u8],
+        payload_bytes: usize,
+    ) -> ResponseForQueryingMessages {
+        let (payload, payload_encoding) = match std::str::from_utf8(payload) {
+            Ok(text) => (text.to_string(), "string"),
+            Err(_) => (BASE64_STANDARD.encode(payload), "base64"),
+        };
+        common::message(json!({
+            "payload_bytes": payload_bytes,
+            "exchange": "orders",
+            "routing_key": "orders.created",
+            "properties": {
+                "content_type": "application/json",
+                "content_encoding": content_encoding
+            },
+            "payload": payload,
+            "payload_encoding": payload_encoding
+        }))
+    }
+
+    #[test]
+    fn test_supported_encodings_are_decompressed() {
+        for (name, encoding) in [
+            ("gzip", ContentEncoding::Gzip),
+            ("x-gzip", ContentEncoding::Gzip),
+            ("deflate", ContentEncoding::Deflate),
+            ("br", ContentEncoding::Brotli),
+            ("zstd", ContentEncoding::Zstd),
+        ] {
+            assert_eq!(ContentEncoding::parse(name), Some(encoding));
+            let compressed = compress(encoding, CONTENT);
+            let decompressed = encoding.decompress(&compressed, false, 1024).unwrap();
+            assert_eq!(decompressed.bytes, CONTENT, "{}", name);
+            assert!(decompressed.complete, "{}", name);
+        }
+        assert_eq!(ContentEncoding::parse("identity"), None);
+    }
+
+    #[test]
+    fn test_raw_deflate_is_decompressed() {
+        let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+        encoder.write_all(CONTENT).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let decompressed = ContentEncoding::Deflate
+            .decompress(&compressed, false, 1024)
+            .unwrap();
+        assert_eq!(decompressed.bytes, CONTENT);
+    }
+
+    #[test]
+    fn test_decompression_is_limited() {
+        let content = vec![b'a'; 10_000];
+        let compressed = gzip(&content);
+
+        let limited = ContentEncoding::Gzip
+            .decompress(&compressed, false, 100)
+            .unwrap();
+        assert_eq!(limited.bytes.len(), 100);
+        assert!(!limited.complete);
+
+        let exact = ContentEncoding::Gzip
+            .decompress(&compressed, false, 10_000)
+            .unwrap();
+        assert!(exact.complete);
+
+        let truncated = ContentEncoding::Gzip
+            .decompress(&compressed[..compressed.len() - 4], true, 20_000)
+            .unwrap();
+        assert!(!truncated.complete);
+        assert!(ContentEncoding::Gzip
+            .decompress(&compressed[..compressed.len() - 4], false, 20_000)
+            .is_err());
+    }
+
+    #[test]
+    fn test_decoded_message_keeps_original_metadata() {
+        let compressed = gzip(CONTENT);
+        let message = message("gzip", &compressed, compressed.len()).decode_content(
+            PayloadEncoding::Auto,
+            None,
+            &mut DecodeBudget::default(),
+        );
+
+        assert_eq!(message.payload().as_bytes(), CONTENT);
+        assert_eq!(message.payload_encoding(), "string");
+        assert_eq!(message.payload_bytes(), compressed.len() as u64);
+        assert_eq!(message.properties().content_encoding(), Some("gzip"));
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(
+            value["content_decoding"],
+            json!({
+                "content_encoding": "gzip",
+                "encoded_payload_encoding": "base64",
+                "encoded_bytes": compressed.len(),
+                "decoded_bytes": CONTENT.len(),
+                "complete": true
+            })
+        );
+
+        let base64 = self::message("gzip", &compressed, compressed.len()).decode_content(
+            PayloadEncoding::Base64,
+            Some(8),
+            &mut DecodeBudget::default(),
+        );
+        assert_eq!(base64.payload(), BASE64_STANDARD.encode(&CONTENT[..8]));
+        assert_eq!(base64.payload_encoding(), "base64");
+        assert!(!base64.content_decoding().unwrap().complete);
+    }
+
+    #[test]
+    fn test_undecodable_messages_are_left_alone() {
+        let plain = message("utf-8", CONTENT, CONTENT.len()).decode_content(
+            PayloadEncoding::Auto,
+            None,
+            &mut DecodeBudget::default(),
+        );
+        assert_eq!(plain.payload().as_bytes(), CONTENT);
+        assert!(plain.content_decoding().is_none());
+        assert!(serde_json::to_value(&plain)
+            .unwrap()
+            .get("content_decoding")
+            .is_none());
+
+        let corrupt = message("gzip", CONTENT, CONTENT.len()).decode_content(
+            PayloadEncoding::Auto,
+            None,
+            &mut DecodeBudget::default(),
+        );
+        assert_eq!(corrupt.payload().as_bytes(), CONTENT);
+        assert!(corrupt.content_decoding().is_none());
+    }
+
+    #[test]
+    fn test_filters_match_decompressed_payloads() {
+        let compressed = compress(ContentEncoding::Zstd, CONTENT);
+        let filter = MessageFilter {
+            payload_contains: Some("cups".to_string()),
+            json_path: Some("$.order[?@ == 7]".to_string()),
+            ..Default::default()
+        };
+
+        let matcher = filter.compile().unwrap();
+        assert!(matcher.reads_payload());
+        let mut message = message("zstd", &compressed, compressed.len());
+        assert!(!matcher.matches(&message));
+
+        let mut budget = DecodeBudget::default();
+        message.decompress(&mut budget);
+        assert_eq!(message.content().unwrap(), CONTENT);
+        assert!(matcher.matches(&message));
+        assert_eq!(
+            budget.remaining(),
+            DecodeBudget::default().remaining() - CONTENT.len() as u64
+        );
+    }
+
+    #[test]
+    fn test_fetched_messages_are_filtered_on_their_decompressed_payloads() {
+        let matching = compress(ContentEncoding::Gzip, CONTENT);
+        let other = compress(ContentEncoding::Gzip, br#"{"order": {"id": 8}}"#);
+        let filter = MessageFilter {
+            payload_contains: Some("cups".to_string()),
+            ..Default::default()
+        };
+
+        let fetched = vec![
+            message("gzip", &other, other.len()),
+            message("gzip", &matching, matching.len()),
+        ];
+        let mut budget = DecodeBudget::default();
+        let filtered = FilteredMessages::filter(fetched, &filter.compile().unwrap(), &mut budget);
+        assert_eq!(filtered.scanned, 2);
+        assert_eq!(filtered.messages.len(), 1);
+        assert_eq!(filtered.messages[0].content().unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn test_payloads_are_decompressed_once_within_the_budget() {
+        let compressed = gzip(CONTENT);
+        let mut budget = DecodeBudget::new(CONTENT.len() as u64 + 10);
+
+        let mut first = message("gzip", &compressed, compressed.len());
+        first.decompress(&mut budget);
+        assert_eq!(budget.remaining(), 10);
+        // The content decompressed for the filters is reused.
+        let first = first.decode_content(PayloadEncoding::Auto, None, &mut budget);
+        assert_eq!(budget.remaining(), 10);
+        assert_eq!(first.payload().as_bytes(), CONTENT);
+
+        let second = message("gzip", &compressed, compressed.len()).decode_content(
+            PayloadEncoding::Auto,
+            None,
+            &mut budget,
+        );
+        assert_eq!(second.payload().as_bytes(), &CONTENT[..10]);
+        assert!(!second.content_decoding().unwrap().complete);
+        assert_eq!(budget.remaining(), 0);
+
+        let third = message("gzip", &compressed, compressed.len()).decode_content(
+            PayloadEncoding::Auto,
+            None,
+            &mut budget,
+        );
+        assert!(third.content_decoding().is_none());
+        assert_eq!(third.payload_encoding(), "base64");
+    }
+}
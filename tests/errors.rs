@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::{
+        client::ManagementClient,
+        exceptions::{ErrorBody, ServerError},
+        prepare_url, send_get,
+    };
+    use rocket::http::Status;
+
+    #[tokio::test]
+    async fn test_send_get_unreachable_host_is_transport_error() {
+        let result: Result<serde_json::Value, ServerError> =
+            send_get("http://127.0.0.1:1/api/vhosts", None).await;
+        assert!(matches!(result, Err(ServerError::Transport { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_client_unreachable_host_is_transport_error() {
+        let client = ManagementClient::new("http://127.0.0.1:1", "guest", "guest").unwrap();
+        let result = client.get_vhosts().await;
+        assert!(matches!(result, Err(ServerError::Transport { .. })));
+    }
+
+    #[test]
+    fn test_prepare_url_rejects_invalid_uri() {
+        let result = prepare_url("http://localhost:15672", "api/queues/my vhost");
+        assert!(matches!(result, Err(ServerError::InvalidUri { .. })));
+    }
+
+    #[test]
+    fn test_upstream_errors_map_to_http_statuses() {
+        let upstream = |status| ServerError::UpstreamStatus {
+            uri: "http://localhost:15672/api/queues/%2F".to_string(),
+            status,
+            body: String::new(),
+        };
+        let timeout = ServerError::Timeout {
+            uri: "http://localhost:15672/api/vhosts".to_string(),
+        };
+
+        assert_eq!(upstream(401).status(), Status::BadGateway);
+        assert_eq!(upstream(403).status(), Status::BadGateway);
+        assert_eq!(upstream(401).status_for(true), Status::Unauthorized);
+        assert_eq!(upstream(403).status_for(true), Status::Forbidden);
+        assert_eq!(upstream(404).status(), Status::NotFound);
+        assert_eq!(upstream(404).status_for(true), Status::NotFound);
+        assert_eq!(upstream(500).status(), Status::BadGateway);
+        assert_eq!(upstream(503).status(), Status::BadGateway);
+        assert_eq!(timeout.status(), Status::GatewayTimeout);
+
+        let body = ErrorBody::from(&upstream(401));
+        assert_eq!(body.code, "upstream_unauthorized");
+        assert_eq!(body.upstream_status, Some(401));
+        assert_eq!(ErrorBody::from(&timeout).upstream_status, None);
+    }
+
+    #[test]
+    fn test_error_bodies_leave_out_upstream_uris_and_bodies() {
+        let uri = "http://rabbitmq.internal:15672/api/queues/%2F/orders";
+        let errors = [
+            ServerError::UpstreamStatus {
+                uri: uri.to_string(),
+                status: 500,
+                body: "{\"reason\": \"node rabbit@host-1 is down\"}".to_string(),
+            },
+            ServerError::Deserialization {
+                uri: uri.to_string(),
+                reason: "expected value".to_string(),
+                body: "<html>node rabbit@host-1</html>".to_string(),
+            },
+            ServerError::Transport {
+                uri: uri.to_string(),
+                reason: "connection refused".to_string(),
+            },
+            ServerError::Timeout {
+                uri: uri.to_string(),
+            },
+        ];
+
+        for error in &errors {
+            assert!(error.to_string().contains(uri));
+            let body = ErrorBody::from(error);
+            assert!(
+                !body.message.contains("rabbitmq.internal"),
+                "{}",
+                body.message
+            );
+            assert!(!body.message.contains("host-1"), "{}", body.message);
+        }
+        let body = ErrorBody::from(&errors[0]);
+        assert_eq!(body.code, "upstream_error");
+        assert_eq!(body.message, "The management API answered with HTTP 500");
+        assert_eq!(body.upstream_status, Some(500));
+    }
+
+    #[test]
+    fn test_login_required_is_unauthorized_without_upstream_status() {
+        let error = ServerError::LoginRequired {
+            cluster: "default".to_string(),
+        };
+        assert_eq!(error.status(), Status::Unauthorized);
+
+        let body = ErrorBody::from(&error);
+        assert_eq!(body.code, "login_required");
+        assert_eq!(body.upstream_status, None);
+    }
+}
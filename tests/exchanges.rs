@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::rabbitmq::exchanges::{MessageToPublish, PublishEncoding};
+    use serde_json::json;
+
+    #[test]
+    fn test_message_to_publish() {
+        let message: MessageToPublish = serde_json::from_value(json!({
+            "routing_key": "orders",
+            "payload": "eyJpZCI6MX0=",
+            "payload_encoding": "base64",
+            "properties": {
+                "content_type": "application/json",
+                "delivery_mode": 2,
+                "timestamp": "2024-01-01T00:00:00Z",
+                "headers": {"x-retry": 1}
+            }
+        }))
+        .unwrap();
+        assert_eq!(message.payload_encoding, PublishEncoding::Base64);
+        assert!(message.validate().is_ok());
+
+        let properties = message.properties.to_publish().unwrap();
+        assert_eq!(
+            serde_json::Value::Object(properties),
+            json!({
+                "content_type": "application/json",
+                "delivery_mode": 2,
+                "timestamp": 1704067200,
+                "headers": {"x-retry": 1}
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_to_publish_defaults() {
+        let message: MessageToPublish =
+            serde_json::from_value(json!({"payload": "hello"})).unwrap();
+        assert_eq!(message.routing_key, "");
+        assert_eq!(message.payload_encoding, PublishEncoding::String);
+        assert!(message.validate().is_ok());
+        assert_eq!(
+            serde_json::Value::Object(message.properties.to_publish().unwrap()),
+            json!({"headers": {}})
+        );
+    }
+
+    #[test]
+    fn test_invalid_base64_payload() {
+        let message: MessageToPublish =
+            serde_json::from_value(json!({"payload": "%%%", "payload_encoding": "base64"}))
+                .unwrap();
+        assert!(message.validate().is_err());
+    }
+}
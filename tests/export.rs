@@ -0,0 +1,160 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use rabbitmq_messages_management::{
+        export::{ExportColumn, ExportEncoder, ExportFormat, DEFAULT_CSV_COLUMNS},
+        rabbitmq::queues::ResponseForQueryingMessages,
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    fn message(payload: &str, payload_encoding: &str) -> ResponseForQueryingMessages {
+        common::message(json!({
+            "payload_bytes": 12,
+            "redelivered": true,
+            "exchange": "orders",
+            "routing_key": "orders.created",
+            "properties": {
+                "content_type": "application/json",
+                "message_id": "id-1",
+                "headers": {"x-tenant": "acme", "x-retry": 2}
+            },
+            "payload": payload,
+            "payload_encoding": payload_encoding
+        }))
+    }
+
+    /// Exports the messages, taking the output after each one like a streamed export does.
+    fn export(
+        format: ExportFormat,
+        columns: &[&str],
+        messages: &[ResponseForQueryingMessages],
+    ) -> Vec<u8> {
+        let columns: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+        let columns = match format {
+            ExportFormat::Csv => ExportColumn::parse_all(&columns).unwrap(),
+            _ => vec![],
+        };
+        let mut encoder = ExportEncoder::new(format, columns).unwrap();
+        let mut output = vec![];
+        for message in messages {
+            encoder.write(message).unwrap();
+            output.extend(encoder.take_output().unwrap());
+        }
+        assert_eq!(encoder.written(), messages.len() as u64);
+        output.extend(encoder.finish().unwrap());
+        output
+    }
+
+    #[test]
+    fn test_ndjson_export() {
+        let messages = [message(r#"{"id": 7}"#, "string"), message("AAE=", "base64")];
+        let output = String::from_utf8(export(ExportFormat::Ndjson, &[], &messages)).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["payload"], r#"{"id": 7}"#);
+        assert_eq!(lines[0]["properties"]["headers"]["x-tenant"], "acme");
+        assert_eq!(lines[1]["payload_encoding"], "base64");
+    }
+
+    #[test]
+    fn test_csv_export() {
+        let messages = [
+            message(r#"{"order": {"id": 7, "lines": [1, 2]}}"#, "string"),
+            message("not, json", "string"),
+        ];
+        let output = export(
+            ExportFormat::Csv,
+            &[
+                "routing_key",
+                "headers.x-retry",
+                "properties.message_id",
+                "$.order.id",
+                "$.order.lines[*]",
+                "payload",
+            ],
+            &messages,
+        );
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let headers = reader.headers().unwrap().clone();
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+
+        assert_eq!(&headers[3], "$.order.id");
+        assert_eq!(
+            rows[0].iter().collect::<Vec<_>>(),
+            vec![
+                "orders.created",
+                "2",
+                "id-1",
+                "7",
+                "[1,2]",
+                r#"{"order": {"id": 7, "lines": [1, 2]}}"#
+            ]
+        );
+        assert_eq!(&rows[1][3], "");
+        assert_eq!(&rows[1][5], "not, json");
+    }
+
+    #[test]
+    fn test_csv_default_columns() {
+        let output = export(ExportFormat::Csv, &[], &[message("{}", "string")]);
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            DEFAULT_CSV_COLUMNS.to_vec()
+        );
+        let row = reader.records().next().unwrap().unwrap();
+        assert_eq!(&row[6], r#"{"x-retry":2,"x-tenant":"acme"}"#);
+    }
+
+    #[test]
+    fn test_invalid_columns() {
+        let columns = ["payload".to_string(), "size".to_string(), "$[".to_string()];
+        match ExportColumn::parse_all(&columns) {
+            Err(error) => assert_eq!(error.status().code, 422),
+            Ok(_) => panic!("invalid columns were accepted"),
+        }
+    }
+
+    #[test]
+    fn test_tar_zstd_export() {
+        let messages = [message("AAH/", "base64"), message(r#"{"id": 7}"#, "string")];
+        let output = export(ExportFormat::TarZstd, &[], &messages);
+        let decoded = zstd::decode_all(output.as_slice()).unwrap();
+        let mut archive = tar::Archive::new(decoded.as_slice());
+
+        let mut files = vec![];
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let path = entry.path().unwrap().display().to_string();
+            let mut contents = vec![];
+            entry.read_to_end(&mut contents).unwrap();
+            files.push((path, contents));
+        }
+
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "000001.message.json",
+                "000001.payload.json",
+                "000002.message.json",
+                "000002.payload.json"
+            ]
+        );
+        assert_eq!(files[1].1, vec![0, 1, 255]);
+        let metadata: serde_json::Value = serde_json::from_slice(&files[0].1).unwrap();
+        assert_eq!(metadata["routing_key"], "orders.created");
+        assert_eq!(metadata["payload_file"], "000001.payload.json");
+        assert!(metadata.get("payload").is_none());
+        assert_eq!(files[3].1, br#"{"id": 7}"#);
+    }
+}
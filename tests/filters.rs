@@ -0,0 +1,102 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::rabbitmq::{
+        filters::MessageFilter, queues::ResponseForQueryingMessages,
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    fn message(payload: &str, payload_encoding: &str) -> ResponseForQueryingMessages {
+        common::message(json!({
+            "redelivered": true,
+            "exchange": "orders",
+            "routing_key": "orders.created.eu",
+            "properties": {
+                "content_type": "application/json; charset=utf-8",
+                "headers": {"x-tenant": "acme", "x-retry": 1}
+            },
+            "payload": payload,
+            "payload_encoding": payload_encoding
+        }))
+    }
+
+    fn matches(filter: serde_json::Value, message: &ResponseForQueryingMessages) -> bool {
+        serde_json::from_value::<MessageFilter>(filter)
+            .unwrap()
+            .compile()
+            .unwrap()
+            .matches(message)
+    }
+
+    #[test]
+    fn test_metadata_filters() {
+        let order = message(r#"{"id": 7, "items": [{"price": 120}]}"#, "string");
+
+        assert!(matches(json!({}), &order));
+        assert!(matches(json!({"routing_key": "orders.*"}), &order));
+        assert!(!matches(json!({"routing_key": "payments.*"}), &order));
+        assert!(matches(
+            json!({"headers": {"x-tenant": "acme", "x-retry": 1}}),
+            &order
+        ));
+        assert!(matches(json!({"headers": {"x-retry": "1"}}), &order));
+        assert!(!matches(json!({"headers": {"x-tenant": "globex"}}), &order));
+        assert!(!matches(json!({"headers": {"x-missing": null}}), &order));
+        assert!(matches(json!({"has_header": ["x-retry"]}), &order));
+        assert!(!matches(json!({"has_header": ["x-missing"]}), &order));
+        assert!(matches(json!({"content_type": "Application/JSON"}), &order));
+        assert!(!matches(json!({"content_type": "text/plain"}), &order));
+        assert!(matches(json!({"redelivered": true}), &order));
+        assert!(!matches(json!({"redelivered": false}), &order));
+    }
+
+    #[test]
+    fn test_payload_filters() {
+        let order = message(r#"{"id": 7, "items": [{"price": 120}]}"#, "string");
+
+        assert!(matches(json!({"payload_contains": "\"id\": 7"}), &order));
+        assert!(!matches(json!({"payload_contains": "\"id\": 8"}), &order));
+        assert!(matches(
+            json!({"payload_regex": r#""price": \d{3}"#}),
+            &order
+        ));
+        assert!(!matches(
+            json!({"payload_regex": r#""price": \d{4}"#}),
+            &order
+        ));
+        assert!(matches(
+            json!({"json_path": "$.items[?@.price > 100]"}),
+            &order
+        ));
+        assert!(!matches(
+            json!({"json_path": "$.items[?@.price > 200]"}),
+            &order
+        ));
+        assert!(!matches(
+            json!({"json_path": "$.id"}),
+            &message("not json", "string")
+        ));
+    }
+
+    #[test]
+    fn test_base64_payloads_are_decoded() {
+        // {"id": 7}
+        let order = message("eyJpZCI6IDd9", "base64");
+        assert!(matches(json!({"payload_contains": "\"id\": 7"}), &order));
+        assert!(matches(json!({"json_path": "$[?@ == 7]"}), &order));
+    }
+
+    #[test]
+    fn test_invalid_filters_are_rejected() {
+        let filter = MessageFilter {
+            payload_regex: Some("(".to_string()),
+            json_path: Some("$[".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.is_empty());
+        assert!(filter.compile().is_err());
+    }
+}
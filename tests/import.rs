@@ -0,0 +1,207 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use rabbitmq_messages_management::{
+        client::ManagementClient,
+        config::ImportConfig,
+        exceptions::ServerError,
+        import::{ImportDestination, ImportLedger, ImportOptions, ImportRecord, ImportTarget},
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    fn target(destination: ImportDestination) -> ImportTarget {
+        ImportTarget {
+            cluster: "local".to_string(),
+            vhost: "/".to_string(),
+            destination,
+        }
+    }
+
+    fn options(dry_run: bool) -> ImportOptions {
+        ImportOptions {
+            rate: 1000,
+            dry_run,
+            force: false,
+            max_bytes: 1024 * 1024,
+        }
+    }
+
+    fn line(message_id: &str, exchange: &str) -> String {
+        json!({
+            "payload": "aGk=",
+            "payload_encoding": "base64",
+            "exchange": exchange,
+            "routing_key": "orders.created",
+            "redelivered": true,
+            "properties": {"message_id": message_id}
+        })
+        .to_string()
+    }
+
+    fn client() -> ManagementClient {
+        ManagementClient::new("http://127.0.0.1:1", "guest", "guest").unwrap()
+    }
+
+    #[test]
+    fn test_exported_messages_can_be_imported() {
+        let message = common::message(json!({
+            "message_count": 3,
+            "properties": {"message_id": "id-1", "timestamp": 1727776800},
+            "payload": "hi"
+        }));
+        let exported = serde_json::to_string(&message).unwrap();
+        let record: ImportRecord = serde_json::from_str(&exported).unwrap();
+
+        assert_eq!(record.payload, "hi");
+        assert_eq!(record.properties.message_id(), Some("id-1"));
+        assert_eq!(
+            ImportDestination::default().route(&record),
+            ("amq.default".to_string(), "orders".to_string())
+        );
+        let destination = ImportDestination {
+            exchange: Some("replay".to_string()),
+            routing_key: None,
+        };
+        assert_eq!(
+            destination.route(&record),
+            ("replay".to_string(), "orders".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ledger() {
+        let ledger = ImportLedger::new(Duration::from_secs(60));
+        let original = target(ImportDestination::default());
+        let replay = target(ImportDestination {
+            exchange: Some("replay".to_string()),
+            routing_key: None,
+        });
+
+        ledger.record(&original, "id-1");
+        assert!(ledger.contains(&original, "id-1"));
+        assert!(!ledger.contains(&original, "id-2"));
+        assert!(!ledger.contains(&replay, "id-1"));
+
+        let expired = ImportLedger::new(Duration::ZERO);
+        expired.record(&original, "id-1");
+        assert!(!expired.contains(&original, "id-1"));
+    }
+
+    #[test]
+    fn test_ledger_survives_restarts() {
+        let path = std::env::temp_dir().join(format!("viewer-ledger-{}.jsonl", std::process::id()));
+        let expired = json!({
+            "time": "2020-01-01T00:00:00Z",
+            "cluster": "local",
+            "vhost": "/",
+            "exchange": null,
+            "routing_key": null,
+            "message_id": "id-0"
+        });
+        fs::write(&path, format!("{}\n{{\"truncated\n", expired)).unwrap();
+        let config = ImportConfig {
+            ledger_path: Some(path.clone()),
+            ..Default::default()
+        };
+        let original = target(ImportDestination::default());
+        let replay = target(ImportDestination {
+            exchange: Some("replay".to_string()),
+            routing_key: None,
+        });
+
+        let ledger = ImportLedger::open(&config).unwrap();
+        assert!(!ledger.contains(&original, "id-0"));
+        ledger.record(&original, "id-1");
+        ledger.record(&replay, "id-2");
+        ledger.flush().unwrap();
+        drop(ledger);
+
+        let ledger = ImportLedger::open(&config).unwrap();
+        assert!(ledger.contains(&original, "id-1"));
+        assert!(ledger.contains(&replay, "id-2"));
+        assert!(!ledger.contains(&original, "id-2"));
+        // Expired and unreadable lines were dropped from the file.
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_reports_every_line() {
+        let ledger = ImportLedger::new(Duration::from_secs(60));
+        let target = target(ImportDestination::default());
+        ledger.record(&target, "id-0");
+        let file = [
+            line("id-0", "orders"),
+            line("id-1", ""),
+            String::new(),
+            "{not json".to_string(),
+            line("id-1", "orders"),
+            json!({"payload": "not base64!", "payload_encoding": "base64"}).to_string(),
+            line("id-2", "orders"),
+        ]
+        .join("\n");
+
+        let report = client()
+            .import_messages(&target, file.as_bytes(), &options(true), &ledger)
+            .await;
+
+        assert!(report.dry_run);
+        assert_eq!(report.lines, 6);
+        assert_eq!(report.published, 2);
+        assert_eq!(report.skipped, 2);
+        assert_eq!(report.failed, 2);
+        assert_eq!(
+            report.errors.iter().map(|e| e.line).collect::<Vec<_>>(),
+            vec![4, 6]
+        );
+        assert_eq!(report.preview[0].line, 2);
+        assert_eq!(report.preview[0].exchange, "amq.default");
+        assert_eq!(report.preview[1].message_id.as_deref(), Some("id-2"));
+        assert!(report.error.is_none());
+        // A dry run does not remember the messages.
+        assert!(!ledger.contains(&target, "id-2"));
+    }
+
+    #[tokio::test]
+    async fn test_import_stops_on_broker_failure() {
+        let ledger = ImportLedger::new(Duration::from_secs(60));
+        let target = target(ImportDestination::default());
+        let file = [line("id-1", "orders"), line("id-2", "orders")].join("\n");
+
+        let report = client()
+            .import_messages(&target, file.as_bytes(), &options(false), &ledger)
+            .await;
+
+        assert_eq!(report.lines, 1);
+        assert_eq!(report.published, 0);
+        assert!(matches!(report.error, Some(ServerError::Transport { .. })));
+        assert!(!ledger.contains(&target, "id-1"));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_files_that_are_too_large() {
+        let ledger = ImportLedger::new(Duration::from_secs(60));
+        let target = target(ImportDestination::default());
+        let first = line("id-1", "orders");
+        let file = [first.clone(), line("id-2", "orders")].join("\n");
+        let options = ImportOptions {
+            max_bytes: first.len() as u64 + 1,
+            ..options(true)
+        };
+
+        let report = client()
+            .import_messages(&target, file.as_bytes(), &options, &ledger)
+            .await;
+
+        assert_eq!(report.published, 1);
+        assert!(matches!(
+            report.error,
+            Some(ServerError::InvalidRequest { .. })
+        ));
+    }
+}
@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod tests {
+    use isahc::http::{HeaderMap, HeaderValue};
+    use rabbitmq_messages_management::logging::{
+        next_request_id, redact_body, redact_headers, REDACTED,
+    };
+
+    #[test]
+    fn test_redact_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            HeaderValue::from_static("Basic Z3Vlc3Q6Z3Vlc3Q="),
+        );
+        headers.insert("cookie", HeaderValue::from_static("session=secret"));
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+
+        let logged = redact_headers(&headers);
+        assert!(!logged.contains("Z3Vlc3Q6Z3Vlc3Q="));
+        assert!(!logged.contains("secret"));
+        assert!(logged.contains(&format!("authorization: {}", REDACTED)));
+        assert!(logged.contains("content-type: application/json"));
+    }
+
+    #[test]
+    fn test_redact_body() {
+        let fields = vec!["password".to_string(), "payload".to_string()];
+        let body =
+            r#"{"name":"bob","password":"secret","messages":[{"payload":"card","count":1}]}"#;
+
+        let logged: serde_json::Value = serde_json::from_str(&redact_body(body, &fields)).unwrap();
+        assert_eq!(logged["name"], "bob");
+        assert_eq!(logged["password"], REDACTED);
+        assert_eq!(logged["messages"][0]["payload"], REDACTED);
+        assert_eq!(logged["messages"][0]["count"], 1);
+        assert_eq!(redact_body("password=secret", &fields), "<15 bytes>");
+    }
+
+    #[test]
+    fn test_next_request_id() {
+        let first = next_request_id();
+        let second = next_request_id();
+        assert_ne!(first, second);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit() || c == '-'));
+    }
+}
@@ -0,0 +1,154 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::rabbitmq::queues::{
+        AckMode, PayloadEncoding, RabbitMQMessage, ResponseForQueryingMessages,
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    fn message() -> serde_json::Value {
+        common::message_json(json!({
+            "redelivered": true,
+            "exchange": "orders",
+            "routing_key": "orders.created.eu",
+            "message_count": 41,
+            "properties": {
+                "priority": 1,
+                "delivery_mode": 2,
+                "headers": {"x-tenant": "acme"},
+                "content_type": "application/json"
+            },
+            "payload": "{\"order\": 1234}"
+        }))
+    }
+
+    #[test]
+    fn test_response_keeps_message_metadata() {
+        let message: RabbitMQMessage = serde_json::from_value(message()).unwrap();
+        let response = ResponseForQueryingMessages::from(message);
+        let value = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(value["payload"], "{\"order\": 1234}");
+        assert_eq!(value["payload_encoding"], "string");
+        assert_eq!(value["exchange"], "orders");
+        assert_eq!(value["routing_key"], "orders.created.eu");
+        assert_eq!(value["redelivered"], true);
+        assert_eq!(value["message_count"], 41);
+        assert_eq!(value["properties"]["headers"]["x-tenant"], "acme");
+        assert_eq!(value["properties"]["content_type"], "application/json");
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_delivery_state() {
+        let first: RabbitMQMessage = serde_json::from_value(message()).unwrap();
+        let mut redelivered = message();
+        redelivered["redelivered"] = json!(false);
+        redelivered["message_count"] = json!(3);
+        let redelivered: RabbitMQMessage = serde_json::from_value(redelivered).unwrap();
+        let mut other = message();
+        other["properties"]["headers"]["x-tenant"] = json!("globex");
+        let other: RabbitMQMessage = serde_json::from_value(other).unwrap();
+
+        let fingerprint = ResponseForQueryingMessages::from(first).fingerprint();
+        assert_eq!(
+            fingerprint,
+            ResponseForQueryingMessages::from(redelivered).fingerprint()
+        );
+        assert_ne!(
+            fingerprint,
+            ResponseForQueryingMessages::from(other).fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_delivery_count() {
+        let first: RabbitMQMessage = serde_json::from_value(message()).unwrap();
+        let mut requeued = message();
+        requeued["properties"]["headers"]["x-delivery-count"] = json!(1);
+        let requeued: RabbitMQMessage = serde_json::from_value(requeued).unwrap();
+        let mut again = message();
+        again["properties"]["headers"]["x-delivery-count"] = json!(2);
+        again["properties"]["headers"]["x-acquired-count"] = json!(3);
+        let again: RabbitMQMessage = serde_json::from_value(again).unwrap();
+
+        let fingerprint = ResponseForQueryingMessages::from(first).fingerprint();
+        assert_eq!(
+            fingerprint,
+            ResponseForQueryingMessages::from(requeued).fingerprint()
+        );
+        assert_eq!(
+            fingerprint,
+            ResponseForQueryingMessages::from(again).fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_all_basic_properties() {
+        let mut message = message();
+        message["properties"] = json!({
+            "content_type": "application/json",
+            "content_encoding": "gzip",
+            "delivery_mode": 2,
+            "priority": 5,
+            "correlation_id": "c-1",
+            "reply_to": "amq.rabbitmq.reply-to",
+            "expiration": "60000",
+            "message_id": "m-1",
+            "timestamp": 1700000000,
+            "type": "order.created",
+            "user_id": "guest",
+            "app_id": "orders-service",
+            "cluster_id": "legacy"
+        });
+        let message: RabbitMQMessage = serde_json::from_value(message).unwrap();
+        let value = serde_json::to_value(ResponseForQueryingMessages::from(message)).unwrap();
+        let properties = &value["properties"];
+
+        assert_eq!(properties["message_id"], "m-1");
+        assert_eq!(properties["correlation_id"], "c-1");
+        assert_eq!(properties["type"], "order.created");
+        assert_eq!(properties["content_encoding"], "gzip");
+        assert_eq!(properties["timestamp"], "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_missing_properties_use_defaults() {
+        let mut message = message();
+        message["properties"] = json!({"content_type": "text/plain"});
+        assert!(serde_json::from_value::<RabbitMQMessage>(message.clone()).is_ok());
+
+        // The management API returns an empty list when a message has no properties at all.
+        message["properties"] = json!([]);
+        assert!(serde_json::from_value::<RabbitMQMessage>(message).is_ok());
+    }
+
+    #[test]
+    fn test_rfc3339_timestamp_round_trips() {
+        let mut message = message();
+        message["properties"] = json!({"timestamp": "2023-11-14T22:13:20Z"});
+        let message: RabbitMQMessage = serde_json::from_value(message).unwrap();
+        let value = serde_json::to_value(ResponseForQueryingMessages::from(message)).unwrap();
+        assert_eq!(value["properties"]["timestamp"], "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_ack_modes_use_management_api_names() {
+        let cases = [
+            (AckMode::AckRequeueTrue, "ack_requeue_true", false),
+            (AckMode::AckRequeueFalse, "ack_requeue_false", true),
+            (AckMode::RejectRequeueTrue, "reject_requeue_true", false),
+            (AckMode::RejectRequeueFalse, "reject_requeue_false", true),
+        ];
+        for (mode, name, destructive) in cases {
+            assert_eq!(serde_json::to_value(mode).unwrap(), name);
+            assert_eq!(mode.is_destructive(), destructive);
+        }
+        assert_eq!(
+            serde_json::to_value(PayloadEncoding::Base64).unwrap(),
+            "base64"
+        );
+    }
+}
@@ -0,0 +1,65 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::rabbitmq::{
+        moves::{dead_letter_origin, MoveDestination, MoveRequest},
+        queues::ResponseForQueryingMessages,
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    fn message(headers: serde_json::Value) -> ResponseForQueryingMessages {
+        common::message(json!({
+            "routing_key": "orders.dlq",
+            "properties": {"headers": headers}
+        }))
+    }
+
+    #[test]
+    fn test_dead_letter_origin() {
+        let dead = message(json!({"x-death": [
+            {"exchange": "orders", "routing-keys": ["orders.created"], "queue": "orders", "count": 2},
+            {"exchange": "", "routing-keys": ["first"], "queue": "first", "count": 1}
+        ]}));
+        assert_eq!(
+            dead_letter_origin(&dead),
+            Some(("orders".to_string(), "orders.created".to_string()))
+        );
+
+        let from_default = message(json!({"x-death": [
+            {"exchange": "", "routing-keys": ["orders"], "queue": "orders", "count": 1}
+        ]}));
+        assert_eq!(
+            dead_letter_origin(&from_default),
+            Some(("amq.default".to_string(), "orders".to_string()))
+        );
+
+        assert_eq!(dead_letter_origin(&message(json!({}))), None);
+        assert_eq!(
+            dead_letter_origin(&message(json!({"x-death": "bad"}))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_move_request() {
+        let request: MoveRequest = serde_json::from_value(json!({
+            "count": 10,
+            "destination": "dead_letter_origin"
+        }))
+        .unwrap();
+        assert_eq!(request.destination, MoveDestination::DeadLetterOrigin);
+        assert!(request.filter.is_empty());
+        assert!(request.validate("orders.dlq").is_ok());
+
+        let request: MoveRequest = serde_json::from_value(json!({
+            "count": 0,
+            "destination": {"queue": "orders.dlq"}
+        }))
+        .unwrap();
+        assert!(request.validate("orders.dlq").is_err());
+        assert!(request.validate("orders").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::{prepare_url, rabbitmq::path::ApiPath};
+
+    #[test]
+    fn test_default_vhost_is_encoded() {
+        assert_eq!(ApiPath::queues("/").to_string(), "api/queues/%2F");
+    }
+
+    #[test]
+    fn test_exchange_publish_path() {
+        assert_eq!(
+            ApiPath::exchange_publish("/", "amq.default").to_string(),
+            "api/exchanges/%2F/amq.default/publish"
+        );
+    }
+
+    #[test]
+    fn test_awkward_queue_names_are_encoded() {
+        let cases = [
+            ("orders/eu", "orders%2Feu"),
+            ("orders#1", "orders%231"),
+            ("orders?x=1", "orders%3Fx%3D1"),
+            ("my queue", "my%20queue"),
+            ("100%", "100%25"),
+            ("café", "caf%C3%A9"),
+            ("a.b-c_d~e", "a.b-c_d~e"),
+        ];
+        for (name, encoded) in cases {
+            assert_eq!(
+                ApiPath::queue_get("/", name).to_string(),
+                format!("api/queues/%2F/{}/get", encoded)
+            );
+        }
+    }
+
+    #[test]
+    fn test_encoded_paths_are_valid_urls() {
+        let path = ApiPath::queue_get("my vhost/1", "q #?/ 1");
+        let url = prepare_url("http://localhost:15672/", &path.to_string()).unwrap();
+        assert_eq!(
+            url,
+            "http://localhost:15672/api/queues/my%20vhost%2F1/q%20%23%3F%2F%201/get"
+        );
+    }
+
+    #[test]
+    fn test_root_trailing_slashes_are_normalised() {
+        for root in [
+            "http://localhost:15672",
+            "http://localhost:15672/",
+            "http://localhost:15672//",
+        ] {
+            assert_eq!(
+                prepare_url(root, &ApiPath::vhosts().to_string()).unwrap(),
+                "http://localhost:15672/api/vhosts"
+            );
+        }
+    }
+}
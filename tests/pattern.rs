@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::pattern::glob_matches;
+
+    #[test]
+    fn test_glob_matches() {
+        let cases = [
+            ("*", "", true),
+            ("*", "orders/eu", true),
+            ("orders", "orders", true),
+            ("orders", "orders-eu", false),
+            ("orders-*", "orders-", true),
+            ("*-eu", "orders-eu", true),
+            ("*-eu", "orders-us", false),
+            ("o*s*u", "orders-eu", true),
+            ("o*s*x", "orders-eu", false),
+            ("q?", "q1", true),
+            ("q?", "q", false),
+            ("café*", "café-1", true),
+        ];
+        for (pattern, text, expected) in cases {
+            assert_eq!(
+                glob_matches(pattern, text),
+                expected,
+                "{} ~ {}",
+                pattern,
+                text
+            );
+        }
+    }
+}
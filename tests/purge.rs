@@ -0,0 +1,127 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, time::Duration};
+
+    use rabbitmq_messages_management::{
+        purge::{write_snapshot, PurgeConfirmations},
+        rabbitmq::exchanges::MessageToPublish,
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    #[test]
+    fn test_confirmation_is_single_use() {
+        let confirmations = PurgeConfirmations::new(Duration::from_secs(60));
+        let confirmation = confirmations.issue("alice", "prod", "/", "orders", 12, 10);
+        assert_eq!(confirmation.messages, 12);
+        assert_eq!(confirmation.messages_ready, 10);
+
+        let token = &confirmation.token;
+        assert!(confirmations
+            .redeem(token, "alice", "prod", "/", "orders")
+            .is_ok());
+        assert!(confirmations
+            .redeem(token, "alice", "prod", "/", "orders")
+            .is_err());
+    }
+
+    #[test]
+    fn test_confirmation_is_used_up_only_once_redeemed() {
+        let confirmations = PurgeConfirmations::new(Duration::from_secs(60));
+        let token = confirmations
+            .issue("alice", "prod", "/", "orders", 1, 1)
+            .token;
+        for _ in 0..2 {
+            assert!(confirmations
+                .check(&token, "alice", "prod", "/", "orders")
+                .is_ok());
+        }
+        assert!(confirmations
+            .check(&token, "bob", "prod", "/", "orders")
+            .is_err());
+
+        // A purge that failed gives its token back.
+        let redeemed = confirmations
+            .redeem(&token, "alice", "prod", "/", "orders")
+            .unwrap();
+        assert!(confirmations
+            .check(&token, "alice", "prod", "/", "orders")
+            .is_err());
+        confirmations.reinstate(redeemed);
+        assert!(confirmations
+            .redeem(&token, "alice", "prod", "/", "orders")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_confirmation_is_bound_to_principal_and_queue() {
+        let confirmations = PurgeConfirmations::new(Duration::from_secs(60));
+        for (principal, cluster, vhost, queue) in [
+            ("bob", "prod", "/", "orders"),
+            ("alice", "staging", "/", "orders"),
+            ("alice", "prod", "eu", "orders"),
+            ("alice", "prod", "/", "payments"),
+        ] {
+            let token = confirmations
+                .issue("alice", "prod", "/", "orders", 1, 1)
+                .token;
+            assert!(
+                confirmations
+                    .redeem(&token, principal, cluster, vhost, queue)
+                    .is_err(),
+                "{} {} {} {}",
+                principal,
+                cluster,
+                vhost,
+                queue
+            );
+        }
+        assert!(confirmations
+            .redeem("made-up", "alice", "prod", "/", "orders")
+            .is_err());
+    }
+
+    #[test]
+    fn test_confirmation_expires() {
+        let confirmations = PurgeConfirmations::new(Duration::ZERO);
+        let token = confirmations
+            .issue("alice", "prod", "/", "orders", 1, 1)
+            .token;
+        assert!(confirmations
+            .redeem(&token, "alice", "prod", "/", "orders")
+            .is_err());
+    }
+
+    #[test]
+    fn test_snapshot_lines_can_be_republished() {
+        let dir = std::env::temp_dir().join(format!("viewer-snapshots-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let messages = vec![common::message(json!({
+            "payload_bytes": 3,
+            "redelivered": true,
+            "exchange": "orders",
+            "routing_key": "orders.created",
+            "properties": {"headers": {"x-tenant": "acme"}, "timestamp": 1700000000},
+            "payload": "AAEC",
+            "payload_encoding": "base64"
+        }))];
+
+        let path = write_snapshot(&dir, "prod", "/", "orders/eu", &messages).unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("prod-_-orders_eu-"), "{}", name);
+        assert!(name.ends_with(".ndjson"));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let republished: MessageToPublish = serde_json::from_str(lines[0]).unwrap();
+        assert!(republished.validate().is_ok());
+        assert_eq!(republished.routing_key, "orders.created");
+        assert_eq!(republished.payload, "AAEC");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
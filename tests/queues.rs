@@ -1,6 +1,180 @@
 #[cfg(test)]
 mod tests {
+    use rabbitmq_messages_management::rabbitmq::queues::{Queue, QueueType};
+    use serde_json::json;
 
     #[tokio::test]
     async fn test_get_queues() {}
+
+    #[test]
+    fn test_classic_queue() {
+        let queue: Queue = serde_json::from_value(json!({
+            "arguments": {},
+            "auto_delete": false,
+            "backing_queue_status": {
+                "avg_ack_egress_rate": 0.0,
+                "avg_ack_ingress_rate": 0.0,
+                "avg_egress_rate": 0.0,
+                "avg_ingress_rate": 0.0,
+                "delta": ["delta", "undefined", 0, 0, "undefined"],
+                "len": 3,
+                "mode": "default",
+                "next_seq_id": 3,
+                "q1": 0, "q2": 0, "q3": 0, "q4": 3,
+                "target_ram_count": "infinity"
+            },
+            "durable": true,
+            "exclusive": false,
+            "head_message_timestamp": null,
+            "idle_since": "2024-10-01T10:00:00.000+00:00",
+            "messages": 3,
+            "messages_paged_out": 0,
+            "name": "orders",
+            "node": "rabbit@localhost",
+            "recoverable_slaves": null,
+            "state": "running",
+            "type": "classic",
+            "vhost": "/"
+        }))
+        .unwrap();
+
+        assert_eq!(queue.name(), "orders");
+        assert!(matches!(queue.queue_type(), QueueType::Classic(_)));
+        assert_eq!(serde_json::to_value(&queue).unwrap()["type"], "classic");
+    }
+
+    #[test]
+    fn test_quorum_queue() {
+        let queue: Queue = serde_json::from_value(json!({
+            "arguments": {"x-queue-type": "quorum"},
+            "auto_delete": false,
+            "consumers": 0,
+            "delivery_limit": 20,
+            "durable": true,
+            "exclusive": false,
+            "leader": "rabbit@node-1",
+            "members": ["rabbit@node-1", "rabbit@node-2", "rabbit@node-3"],
+            "messages": 10,
+            "name": "payments",
+            "node": "rabbit@node-1",
+            "online": ["rabbit@node-1", "rabbit@node-2"],
+            "open_files": {"rabbit@node-1": 1},
+            "state": "running",
+            "type": "quorum",
+            "vhost": "finance"
+        }))
+        .unwrap();
+
+        assert!(matches!(queue.queue_type(), QueueType::Quorum(_)));
+        let value = serde_json::to_value(&queue).unwrap();
+        assert_eq!(value["type"], "quorum");
+        assert_eq!(value["leader"], "rabbit@node-1");
+        assert_eq!(value["members"].as_array().unwrap().len(), 3);
+        assert_eq!(value["online"].as_array().unwrap().len(), 2);
+        assert_eq!(value["delivery_limit"], 20);
+        assert_eq!(queue.delivery_limit(), Some(20));
+    }
+
+    #[test]
+    fn test_delivery_limit_set_by_argument_or_policy() {
+        let quorum = |arguments, policy| -> Queue {
+            serde_json::from_value(json!({
+                "arguments": arguments,
+                "effective_policy_definition": policy,
+                "name": "payments",
+                "type": "quorum",
+                "vhost": "/"
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(quorum(json!({}), json!({})).delivery_limit(), None);
+        let by_argument = quorum(json!({"x-delivery-limit": 5}), json!({}));
+        assert_eq!(by_argument.delivery_limit(), Some(5));
+        let by_policy = quorum(json!({}), json!({"delivery-limit": 10}));
+        assert_eq!(by_policy.delivery_limit(), Some(10));
+        let both = quorum(json!({"x-delivery-limit": 5}), json!({"delivery-limit": 3}));
+        assert_eq!(both.delivery_limit(), Some(3));
+    }
+
+    #[test]
+    fn test_stream_queue() {
+        let queue: Queue = serde_json::from_value(json!({
+            "arguments": {"x-queue-type": "stream"},
+            "durable": true,
+            "leader": "rabbit@node-1",
+            "members": ["rabbit@node-1"],
+            "name": "events",
+            "online": ["rabbit@node-1"],
+            "segments": 4,
+            "type": "stream",
+            "vhost": "/"
+        }))
+        .unwrap();
+
+        assert!(matches!(queue.queue_type(), QueueType::Stream(_)));
+        assert_eq!(serde_json::to_value(&queue).unwrap()["segments"], 4);
+    }
+
+    #[test]
+    fn test_unknown_queue_type_is_not_an_error() {
+        let queue: Queue = serde_json::from_value(json!({
+            "name": "mqtt-subscription",
+            "type": "rabbit_mqtt_qos0_queue",
+            "vhost": "/"
+        }))
+        .unwrap();
+
+        assert!(matches!(queue.queue_type(), QueueType::Unknown));
+    }
+
+    #[test]
+    fn test_mixed_queue_types_in_one_vhost() {
+        let queues: Vec<Queue> = serde_json::from_value(json!([
+            {"name": "a", "type": "classic", "vhost": "/"},
+            {"name": "b", "type": "quorum", "vhost": "/", "leader": "rabbit@node-1"},
+            {"name": "c", "type": "stream", "vhost": "/"}
+        ]))
+        .unwrap();
+
+        assert_eq!(queues.len(), 3);
+    }
+
+    #[test]
+    fn test_queue_arguments_and_policy() {
+        let queue: Queue = serde_json::from_value(json!({
+            "arguments": {
+                "x-dead-letter-exchange": "orders.dlx",
+                "x-dead-letter-routing-key": "orders.dead",
+                "x-message-ttl": 60000,
+                "x-overflow": "reject-publish-dlx",
+                "x-single-active-consumer": true,
+                "x-custom-plugin-arg": {"nested": 1}
+            },
+            "effective_policy_definition": {
+                "max-length": 1000,
+                "queue-mode": "lazy",
+                "ha-mode": "all"
+            },
+            "name": "orders",
+            "type": "classic",
+            "vhost": "/"
+        }))
+        .unwrap();
+
+        let value = serde_json::to_value(&queue).unwrap();
+        let arguments = &value["arguments"];
+        assert_eq!(arguments["x-dead-letter-exchange"], "orders.dlx");
+        assert_eq!(arguments["x-dead-letter-routing-key"], "orders.dead");
+        assert_eq!(arguments["x-message-ttl"], 60000);
+        assert_eq!(arguments["x-overflow"], "reject-publish-dlx");
+        assert_eq!(arguments["x-single-active-consumer"], true);
+        assert_eq!(arguments["x-custom-plugin-arg"], json!({"nested": 1}));
+        assert!(arguments.get("x-max-length").is_none());
+
+        let policy = &value["effective_policy_definition"];
+        assert_eq!(policy["max-length"], 1000);
+        assert_eq!(policy["queue-mode"], "lazy");
+        assert_eq!(policy["ha-mode"], "all");
+    }
 }
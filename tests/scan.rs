@@ -0,0 +1,224 @@
+mod common;
+
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::rabbitmq::{
+        filters::{MessageFilter, MessageMatcher},
+        queues::{Queue, ResponseForQueryingMessages},
+        scan::{
+            ensure_reachable, scan_warnings, ScanOptions, ScanState, ScanStop, MAX_SCAN_BATCH_SIZE,
+        },
+    };
+    use serde_json::json;
+
+    use crate::common;
+
+    fn message(payload: &str) -> ResponseForQueryingMessages {
+        common::message(json!({"payload": payload}))
+    }
+
+    fn queue(len: usize, needles: &[usize]) -> Vec<ResponseForQueryingMessages> {
+        (0..len)
+            .map(|i| match needles.contains(&i) {
+                true => message(&format!("needle {}", i)),
+                false => message(&format!("hay {}", i)),
+            })
+            .collect()
+    }
+
+    fn needle() -> MessageMatcher {
+        MessageFilter {
+            payload_contains: Some("needle".to_string()),
+            ..Default::default()
+        }
+        .compile()
+        .unwrap()
+    }
+
+    /// Runs a scan against a simulated queue. `requeue_at_back` tells whether the broker puts the
+    /// fetched messages back at the back of the queue (like quorum queues) or at the head (like
+    /// classic queues).
+    fn run(
+        mut messages: Vec<ResponseForQueryingMessages>,
+        options: ScanOptions,
+        requeue_at_back: bool,
+    ) -> (ScanState, Vec<String>) {
+        let mut state = ScanState::new(options, messages.len() as u64);
+        let mut found = vec![];
+        while let Some(count) = state.next_count() {
+            let count = (count as usize).min(messages.len());
+            let batch = messages[..count].to_vec();
+            if requeue_at_back {
+                messages.rotate_left(count);
+            }
+            found.extend(
+                state
+                    .absorb(batch, &needle())
+                    .iter()
+                    .map(|m| m.payload().to_string()),
+            );
+            assert!(state.progress().batches < 1000, "the scan never stopped");
+        }
+        (state, found)
+    }
+
+    #[test]
+    fn test_scan_reaches_past_a_head_requeued_in_place() {
+        let options = ScanOptions {
+            batch_size: 100,
+            ..Default::default()
+        };
+        let (state, found) = run(queue(1000, &[3, 937]), options, false);
+
+        assert_eq!(found, vec!["needle 3", "needle 937"]);
+        let progress = state.progress();
+        assert_eq!(progress.scanned, 1000);
+        assert_eq!(progress.matched, 2);
+        assert_eq!(progress.stopped, Some(ScanStop::Completed));
+        assert!(progress.deliveries > 1000);
+    }
+
+    #[test]
+    fn test_scan_batches_are_bounded() {
+        let options = ScanOptions {
+            batch_size: 500,
+            limit: 100_000,
+            ..Default::default()
+        };
+        let mut state = ScanState::new(options, 2500);
+        let messages = queue(2500, &[2300]);
+        let mut found = vec![];
+        while let Some(count) = state.next_count() {
+            assert!(
+                count <= MAX_SCAN_BATCH_SIZE,
+                "fetched {} messages at once",
+                count
+            );
+            let batch = messages[..count as usize].to_vec();
+            found.extend(state.absorb(batch, &needle()));
+        }
+
+        // The head is requeued in place, so the messages past the largest batch are out of reach.
+        assert!(found.is_empty());
+        let progress = state.progress();
+        assert_eq!(progress.stopped, Some(ScanStop::HeadRequeued));
+        assert_eq!(progress.scanned, MAX_SCAN_BATCH_SIZE);
+        assert!(progress.deliveries <= 4 * MAX_SCAN_BATCH_SIZE);
+        let warnings = state.take_warnings();
+        assert!(warnings[0].contains("1500 messages were not reached"));
+    }
+
+    #[test]
+    fn test_scan_of_part_of_a_classic_queue_is_incomplete() {
+        // Like an export of `count=100000`, fetching 200 new messages at a time.
+        let options = ScanOptions {
+            batch_size: 200,
+            limit: 100_000,
+            max_matches: 100_000,
+            ..Default::default()
+        };
+        let (state, _) = run(queue(2500, &[]), options.clone(), false);
+        assert_eq!(state.progress().stopped, Some(ScanStop::HeadRequeued));
+        let error = state.progress().ensure_complete().unwrap_err();
+        assert!(error.to_string().contains("only 1000 of the 2500 messages"));
+
+        let (state, _) = run(queue(800, &[]), options, false);
+        assert_eq!(state.progress().stopped, Some(ScanStop::Completed));
+        assert!(state.progress().ensure_complete().is_ok());
+    }
+
+    #[test]
+    fn test_exports_past_the_head_of_a_classic_queue_are_refused() {
+        let queue = |queue_type: &str| -> Queue {
+            serde_json::from_value(json!({
+                "name": "orders",
+                "vhost": "/",
+                "type": queue_type,
+                "messages_ready": 2500
+            }))
+            .unwrap()
+        };
+        let error = ensure_reachable(&queue("classic"), 100_000).unwrap_err();
+        assert_eq!(error.status().code, 409);
+        assert!(error.to_string().contains("2500 ready messages"));
+
+        assert!(ensure_reachable(&queue("classic"), MAX_SCAN_BATCH_SIZE).is_ok());
+        assert!(ensure_reachable(&queue("quorum"), 100_000).is_ok());
+    }
+
+    #[test]
+    fn test_scan_stops_when_a_rotating_queue_wraps_around() {
+        let options = ScanOptions {
+            batch_size: 100,
+            ..Default::default()
+        };
+        let (state, found) = run(queue(1000, &[937]), options, true);
+
+        assert_eq!(found, vec!["needle 937"]);
+        assert_eq!(state.progress().scanned, 1000);
+        assert!(state.progress().stopped.is_some());
+    }
+
+    #[test]
+    fn test_scan_limits() {
+        let options = ScanOptions {
+            batch_size: 100,
+            limit: 250,
+            ..Default::default()
+        };
+        let (state, found) = run(queue(1000, &[937]), options, false);
+        assert!(found.is_empty());
+        assert_eq!(state.progress().scanned, 250);
+        assert_eq!(state.progress().stopped, Some(ScanStop::LimitReached));
+
+        let options = ScanOptions {
+            batch_size: 100,
+            max_matches: 2,
+            ..Default::default()
+        };
+        let (state, found) = run(queue(1000, &[10, 20, 30]), options, true);
+        assert_eq!(found, vec!["needle 10", "needle 20"]);
+        assert_eq!(state.progress().stopped, Some(ScanStop::MatchLimitReached));
+    }
+
+    #[test]
+    fn test_scan_of_an_empty_queue() {
+        let (state, found) = run(vec![], ScanOptions::default(), false);
+        assert!(found.is_empty());
+        assert_eq!(state.progress().batches, 1);
+        assert_eq!(state.progress().stopped, Some(ScanStop::QueueEmpty));
+    }
+
+    #[test]
+    fn test_scan_warns_about_unreached_messages() {
+        // Identical messages have the same fingerprint, so only one of them is examined.
+        let messages = vec![message("same"); 5];
+        let mut state = ScanState::new(ScanOptions::default(), 5);
+        state.absorb(messages, &needle());
+
+        assert_eq!(state.next_count(), None);
+        assert_eq!(state.progress().scanned, 1);
+        let warnings = state.take_warnings();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("only 1 of the 5 messages"));
+        assert!(state.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_scan_warnings() {
+        let queue: Queue = serde_json::from_value(json!({
+            "name": "payments",
+            "vhost": "/",
+            "type": "quorum",
+            "consumers": 2,
+            "delivery_limit": 20
+        }))
+        .unwrap();
+        let warnings = scan_warnings(&queue);
+
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].contains("redelivered"));
+        assert!(warnings[1].contains("delivery limit of 20"));
+        assert!(warnings[2].contains("2 consumers"));
+    }
+}
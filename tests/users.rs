@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod tests {
+    use rabbitmq_messages_management::{
+        client::ManagementClient, exceptions::ServerError, rabbitmq::users::WhoAmI,
+    };
+
+    #[test]
+    fn test_whoami_tags_as_list() {
+        let user: WhoAmI =
+            serde_json::from_str(r#"{"name": "alice", "tags": ["administrator", "monitoring"]}"#)
+                .unwrap();
+        assert_eq!(user.name(), "alice");
+        assert_eq!(user.tags(), ["administrator", "monitoring"]);
+    }
+
+    #[test]
+    fn test_whoami_tags_as_comma_separated_string() {
+        let user: WhoAmI =
+            serde_json::from_str(r#"{"name": "bob", "tags": "management, policymaker"}"#).unwrap();
+        assert_eq!(user.tags(), ["management", "policymaker"]);
+
+        let user: WhoAmI = serde_json::from_str(r#"{"name": "carol", "tags": ""}"#).unwrap();
+        assert!(user.tags().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_client_with_credentials_keeps_root() {
+        let client = ManagementClient::new("http://127.0.0.1:1", "guest", "guest").unwrap();
+        let user_client = client.with_credentials("alice", "secret");
+        assert_eq!(user_client.root(), client.root());
+
+        let result = user_client.whoami().await;
+        assert!(matches!(result, Err(ServerError::Transport { .. })));
+    }
+}